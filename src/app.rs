@@ -0,0 +1,979 @@
+use std::{num::NonZeroU32, sync::Arc, time::Duration};
+
+use egui::{Align2, ColorImage, TextureHandle};
+use fast_image_resize as fr;
+
+use crate::{
+    background::{Background, EnvironmentMap},
+    camera::{Camera, CameraInput},
+    config::ImageConfig,
+    material::Material,
+    object::{Object, World},
+    primitive::{Color, Vec3},
+    render_thread::{Job, RenderThread, Update},
+    sampler::SamplerType,
+    texture::{NoisePattern, Texture, WrapMode},
+    tone_mapping::ToneMapping,
+    tracer::Tracer,
+    Config,
+};
+
+/// Samples per pixel of each pass while running or moving, low enough to stay interactive
+const PREVIEW_SPP: usize = 8;
+
+#[derive(Clone, Copy)]
+struct Point {
+    x: i32,
+    y: i32,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum AppState {
+    Running,
+    Moving,
+    Paused,
+}
+
+impl AppState {
+    fn to_button_str(&self) -> &'static str {
+        match self {
+            AppState::Running => "Pause",
+            AppState::Paused => "Run",
+            AppState::Moving => "Run",
+        }
+    }
+}
+
+pub struct App {
+    frame: Option<TextureHandle>,
+    frame_size: egui::Vec2,
+    last_render_time: Duration,
+    render_thread: RenderThread,
+    /// The image as streamed back from the render thread
+    linear: Vec<Color>,
+    /// `linear`, tone mapped for display
+    pixels: Vec<u8>,
+    samples: usize,
+
+    // Scene being edited, sent to the render thread with every pass
+    camera: Camera,
+    config: ImageConfig,
+    background: Background,
+    world: World,
+    world_snapshot: Arc<World>,
+    /// Whether the next pass should throw away the accumulated samples
+    pending_reset: bool,
+
+    // UI state
+    state: AppState,
+    locked_pos: Option<Point>,
+    skip_mouse_update: bool,
+    save_error: Option<String>,
+}
+
+impl App {
+    pub fn new(config: Config) -> Self {
+        let tracer = Tracer::new(config.image, config.camera, config.background.clone());
+        let world = World::new(config.world);
+        let pixel_count = config.image.width.get() as usize * config.image.height.get() as usize;
+
+        let mut slf = Self {
+            frame: None,
+            frame_size: egui::Vec2::default(),
+            last_render_time: Duration::ZERO,
+            linear: vec![Color::ZERO; pixel_count],
+            pixels: vec![0; pixel_count * 3],
+            samples: 0,
+            camera: tracer.camera,
+            config: config.image,
+            background: config.background,
+            world_snapshot: Arc::new(world.clone()),
+            world,
+            pending_reset: false,
+            render_thread: RenderThread::spawn(tracer),
+            state: AppState::Paused,
+            locked_pos: None,
+            skip_mouse_update: false,
+            save_error: None,
+        };
+        slf.render(slf.config.samples_per_pixel);
+        slf
+    }
+
+    /// Queue a pass adding `spp` samples per pixel on the render thread
+    pub fn render(&mut self, spp: usize) {
+        self.render_thread.render(Job {
+            camera: self.camera,
+            config: self.config,
+            background: self.background.clone(),
+            world: self.world_snapshot.clone(),
+            spp,
+            reset: std::mem::take(&mut self.pending_reset),
+        });
+    }
+
+    /// Apply the updates streamed back from the render thread
+    fn receive_updates(&mut self) {
+        let width = self.config.width.get() as usize;
+        let updates: Vec<_> = self.render_thread.updates().collect();
+        for update in updates {
+            match update {
+                Update::Row { index, pixels } => {
+                    let start = index * width;
+                    self.linear[start..start + width].copy_from_slice(&pixels);
+                    self.tone_map(start..start + width);
+                }
+                Update::Finished {
+                    samples,
+                    render_time,
+                } => {
+                    self.samples = samples;
+                    self.last_render_time = render_time;
+                }
+                Update::Saved(result) => {
+                    self.save_error = result.err().map(|e| e.to_string());
+                }
+            }
+        }
+    }
+
+    /// Convert a range of pixels from the linear image for display
+    fn tone_map(&mut self, range: std::ops::Range<usize>) {
+        let tone_mapping = self.config.tone_mapping;
+        let exposure = self.config.exposure;
+        let rgb = self.pixels[range.start * 3..range.end * 3].chunks_exact_mut(3);
+        for (rgb, &color) in rgb.zip(&self.linear[range]) {
+            rgb.copy_from_slice(&tone_mapping.to_rgb8(color, exposure));
+        }
+    }
+
+    pub fn resize_to_frame(&mut self, ui: &mut egui::Ui) -> TextureHandle {
+        let new_frame_size = ui.available_size();
+
+        // If we have a texture already...
+        if let Some(texture) = &self.frame {
+            // And the size hasn't changed...
+            if new_frame_size == self.frame_size {
+                // Used the already rendered texture
+                return texture.clone();
+            }
+        }
+
+        // Calculate new image size fitting image aspect ratio
+        let (new_width, new_height) = {
+            let old_width = self.config.width.get() as f32;
+            let old_height = self.config.height.get() as f32;
+            let screen_ratio = (new_frame_size.x / old_width).min(new_frame_size.y / old_height);
+
+            (old_width * screen_ratio, old_height * screen_ratio)
+        };
+
+        // Original sized image
+        let image = fr::Image::from_slice_u8(
+            self.config.width,
+            self.config.height,
+            &mut self.pixels,
+            fr::PixelType::U8x3,
+        )
+        .unwrap();
+
+        // Resize image
+        let image_size = [new_width as _, new_height as _];
+        let mut resized = fr::Image::new(
+            NonZeroU32::new(new_width as u32).unwrap(),
+            NonZeroU32::new(new_height as u32).unwrap(),
+            fr::PixelType::U8x3,
+        );
+
+        let mut resizer = fr::Resizer::new(fr::ResizeAlg::Nearest);
+        resizer
+            .resize(&image.view(), &mut resized.view_mut())
+            .unwrap();
+
+        let image = ColorImage::from_rgb(image_size, &resized.buffer());
+        let handle = ui
+            .ctx()
+            .load_texture("raytracer output", image, Default::default());
+        self.frame = Some(handle.clone());
+        handle
+    }
+}
+
+impl eframe::App for App {
+    fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
+        const SPACING: f32 = 10.0;
+
+        self.receive_updates();
+
+        // Whether anything changed that invalidates the accumulated samples
+        let mut scene_changed = false;
+        let mut world_changed = false;
+
+        egui::SidePanel::right("Settings").show(ctx, |ui| {
+            ui.add_space(SPACING);
+            ui.heading("Settings");
+            ui.separator();
+
+            egui::CollapsingHeader::new("Image").show(ui, |ui| {
+                egui::Grid::new("ImageGrid").show(ui, |ui| {
+                    ui.label("Samples Per Pixel");
+                    ui.add(egui::Slider::new(
+                        &mut self.config.samples_per_pixel,
+                        1..=100,
+                    ));
+                    ui.end_row();
+
+                    ui.label("Max Ray Depth");
+                    scene_changed |= ui
+                        .add(egui::Slider::new(&mut self.config.max_ray_depth, 1..=50))
+                        .changed();
+                    ui.end_row();
+
+                    ui.label("Roulette Depth");
+                    scene_changed |= ui
+                        .add(egui::Slider::new(&mut self.config.roulette_depth, 0..=50))
+                        .changed();
+                    ui.end_row();
+
+                    ui.label("Roulette Probability");
+                    scene_changed |= ui
+                        .add(egui::Slider::new(
+                            &mut self.config.roulette_probability,
+                            0.0..=1.0,
+                        ))
+                        .changed();
+                    ui.end_row();
+
+                    ui.label("Seed");
+                    scene_changed |= ui
+                        .add(egui::DragValue::new(&mut self.config.seed))
+                        .changed();
+                    ui.end_row();
+
+                    ui.label("Sampler");
+                    egui::ComboBox::from_id_source("Sampler")
+                        .selected_text(format!("{:?}", self.config.sampler))
+                        .show_ui(ui, |ui| {
+                            for sampler in [
+                                SamplerType::Independent,
+                                SamplerType::Stratified,
+                                SamplerType::Halton,
+                                SamplerType::Sobol,
+                            ] {
+                                scene_changed |= ui
+                                    .selectable_value(
+                                        &mut self.config.sampler,
+                                        sampler,
+                                        format!("{sampler:?}"),
+                                    )
+                                    .changed();
+                            }
+                        });
+                    ui.end_row();
+
+                    // Only affects the display, so no need to render again
+                    if tone_mapping_ui(ui, &mut self.config) {
+                        self.tone_map(0..self.linear.len());
+                    }
+                });
+            });
+
+            egui::CollapsingHeader::new("Camera").show(ui, |ui| {
+                egui::Grid::new("CameraGrid").show(ui, |ui| {
+                    ui.label("Focal length");
+                    let fd = self.camera.config.focus_dist.unwrap();
+                    scene_changed |= ui
+                        .add(
+                            egui::Slider::new(&mut self.camera.focus_dist, (fd * 0.5)..=(fd * 2.0))
+                                .drag_value_speed(0.001)
+                                .fixed_decimals(3)
+                                .step_by(0.001),
+                        )
+                        .changed();
+                    ui.end_row();
+
+                    ui.label("Aperture");
+                    scene_changed |= ui
+                        .add(
+                            egui::Slider::new(&mut self.camera.aperture, 0.0..=4.0)
+                                .drag_value_speed(0.01)
+                                .step_by(0.01),
+                        )
+                        .changed();
+                    ui.end_row();
+
+                    ui.label("Shutter");
+                    ui.horizontal(|ui| {
+                        let config = &mut self.camera.config;
+                        scene_changed |= ui
+                            .add(
+                                egui::DragValue::new(&mut config.shutter_open)
+                                    .speed(0.01)
+                                    .clamp_range(0.0..=config.shutter_close),
+                            )
+                            .changed();
+                        scene_changed |= ui
+                            .add(
+                                egui::DragValue::new(&mut config.shutter_close)
+                                    .speed(0.01)
+                                    .clamp_range(config.shutter_open..=1.0),
+                            )
+                            .changed();
+                    });
+                    ui.end_row();
+                });
+            });
+
+            egui::CollapsingHeader::new("Background").show(ui, |ui| {
+                egui::Grid::new("BackgroundGrid").show(ui, |ui| {
+                    scene_changed |= background_ui(ui, &mut self.background);
+                });
+            });
+
+            egui::CollapsingHeader::new("Objects").show(ui, |ui| {
+                egui::ScrollArea::vertical()
+                    .max_height(ui.available_height() * 0.8)
+                    .show(ui, |ui| {
+                        let mut bounds_changed = false;
+                        for (idx, obj) in self.world.objects_mut().iter_mut().enumerate() {
+                            egui::Grid::new(idx.to_string()).show(ui, |ui| {
+                                let materials = match obj {
+                                    Object::Sphere(s) => {
+                                        ui.label("Center");
+                                        bounds_changed |= vec3_ui(ui, &mut s.center);
+                                        ui.end_row();
+
+                                        ui.label("Radius");
+                                        bounds_changed |= length_ui(ui, &mut s.radius);
+                                        ui.end_row();
+
+                                        std::slice::from_mut(&mut s.material)
+                                    }
+                                    Object::MovingSphere(s) => {
+                                        ui.label("Start");
+                                        bounds_changed |= vec3_ui(ui, &mut s.center0);
+                                        ui.end_row();
+
+                                        ui.label("End");
+                                        bounds_changed |= vec3_ui(ui, &mut s.center1);
+                                        ui.end_row();
+
+                                        ui.label("Radius");
+                                        bounds_changed |= length_ui(ui, &mut s.radius);
+                                        ui.end_row();
+
+                                        std::slice::from_mut(&mut s.material)
+                                    }
+                                    Object::Triangle(t) => {
+                                        for vertex in t.vertices.iter_mut() {
+                                            ui.label("Vertex");
+                                            bounds_changed |= vec3_ui(ui, vertex);
+                                            ui.end_row();
+                                        }
+
+                                        std::slice::from_mut(&mut t.material)
+                                    }
+                                    Object::Quad(q) => {
+                                        ui.label("Origin");
+                                        bounds_changed |= vec3_ui(ui, &mut q.origin);
+                                        ui.end_row();
+
+                                        ui.label("U");
+                                        bounds_changed |= vec3_ui(ui, &mut q.u);
+                                        ui.end_row();
+
+                                        ui.label("V");
+                                        bounds_changed |= vec3_ui(ui, &mut q.v);
+                                        ui.end_row();
+
+                                        std::slice::from_mut(&mut q.material)
+                                    }
+                                    Object::Plane(p) => {
+                                        ui.label("Point");
+                                        world_changed |= vec3_ui(ui, &mut p.point);
+                                        ui.end_row();
+
+                                        ui.label("Normal");
+                                        world_changed |= vec3_ui(ui, &mut p.normal);
+                                        ui.end_row();
+
+                                        std::slice::from_mut(&mut p.material)
+                                    }
+                                    Object::Box(b) => {
+                                        ui.label("Minimum");
+                                        bounds_changed |= vec3_ui(ui, &mut b.minimum);
+                                        ui.end_row();
+
+                                        ui.label("Maximum");
+                                        bounds_changed |= vec3_ui(ui, &mut b.maximum);
+                                        ui.end_row();
+
+                                        std::slice::from_mut(&mut b.material)
+                                    }
+                                    Object::Disk(d) => {
+                                        ui.label("Center");
+                                        bounds_changed |= vec3_ui(ui, &mut d.center);
+                                        ui.end_row();
+
+                                        ui.label("Normal");
+                                        bounds_changed |= vec3_ui(ui, &mut d.normal);
+                                        ui.end_row();
+
+                                        ui.label("Radius");
+                                        bounds_changed |= length_ui(ui, &mut d.radius);
+                                        ui.end_row();
+
+                                        std::slice::from_mut(&mut d.material)
+                                    }
+                                    Object::Cylinder(c) => {
+                                        ui.label("Base");
+                                        bounds_changed |= vec3_ui(ui, &mut c.base);
+                                        ui.end_row();
+
+                                        ui.label("Axis");
+                                        bounds_changed |= vec3_ui(ui, &mut c.axis);
+                                        ui.end_row();
+
+                                        ui.label("Radius");
+                                        bounds_changed |= length_ui(ui, &mut c.radius);
+                                        ui.end_row();
+
+                                        ui.label("Capped");
+                                        world_changed |= ui.checkbox(&mut c.capped, "").changed();
+                                        ui.end_row();
+
+                                        std::slice::from_mut(&mut c.material)
+                                    }
+                                    Object::Cone(c) => {
+                                        ui.label("Base");
+                                        bounds_changed |= vec3_ui(ui, &mut c.base);
+                                        ui.end_row();
+
+                                        ui.label("Axis");
+                                        bounds_changed |= vec3_ui(ui, &mut c.axis);
+                                        ui.end_row();
+
+                                        ui.label("Radius");
+                                        bounds_changed |= length_ui(ui, &mut c.radius);
+                                        ui.end_row();
+
+                                        ui.label("Capped");
+                                        world_changed |= ui.checkbox(&mut c.capped, "").changed();
+                                        ui.end_row();
+
+                                        std::slice::from_mut(&mut c.material)
+                                    }
+                                    Object::Torus(t) => {
+                                        ui.label("Center");
+                                        bounds_changed |= vec3_ui(ui, &mut t.center);
+                                        ui.end_row();
+
+                                        ui.label("Axis");
+                                        bounds_changed |= vec3_ui(ui, &mut t.axis);
+                                        ui.end_row();
+
+                                        ui.label("Major Radius");
+                                        bounds_changed |= length_ui(ui, &mut t.major_radius);
+                                        ui.end_row();
+
+                                        ui.label("Minor Radius");
+                                        bounds_changed |= length_ui(ui, &mut t.minor_radius);
+                                        ui.end_row();
+
+                                        std::slice::from_mut(&mut t.material)
+                                    }
+                                    Object::Mesh(m) => {
+                                        ui.label("Triangles");
+                                        ui.label(m.triangle_count().to_string());
+                                        ui.end_row();
+
+                                        m.materials.as_mut_slice()
+                                    }
+                                    Object::Instance(instance) => {
+                                        if let Some(prototype) = &instance.prototype {
+                                            ui.label("Prototype");
+                                            ui.label(prototype);
+                                            ui.end_row();
+                                        }
+
+                                        ui.label("Translate");
+                                        let mut translation = instance.translation();
+                                        if vec3_ui(ui, &mut translation) {
+                                            instance.set_translation(translation);
+                                            bounds_changed = true;
+                                        }
+                                        ui.end_row();
+
+                                        // The object may be shared with other instances
+                                        &mut []
+                                    }
+                                    Object::ConstantMedium(m) => {
+                                        ui.label("Density");
+                                        world_changed |= ui
+                                            .add(
+                                                egui::DragValue::new(&mut m.density)
+                                                    .speed(0.001)
+                                                    .clamp_range(0.0..=f32::MAX),
+                                            )
+                                            .changed();
+                                        ui.end_row();
+
+                                        std::slice::from_mut(&mut m.material)
+                                    }
+                                };
+                                for material in materials {
+                                    world_changed |= material_ui(ui, material);
+                                }
+                            });
+
+                            ui.separator();
+                        }
+
+                        if bounds_changed {
+                            self.world.rebuild();
+                            world_changed = true;
+                        }
+                    });
+            });
+
+            ui.with_layout(egui::Layout::bottom_up(egui::Align::RIGHT), |ui| {
+                ui.add_space(SPACING);
+                ui.horizontal(|ui| {
+                    if ui.button("Save").clicked() {
+                        let mut dialog = rfd::FileDialog::new()
+                            .set_title("Save render to")
+                            .set_file_name("image.png");
+                        if let Ok(current_dir) = std::env::current_dir() {
+                            dialog = dialog.set_directory(current_dir);
+                        }
+                        if let Some(path) = dialog.save_file() {
+                            self.render_thread.save(path, self.config);
+                        }
+                    }
+
+                    let render_button = ui.add_enabled(
+                        matches!(self.state, AppState::Paused) && !self.render_thread.is_busy(),
+                        egui::Button::new("Render"),
+                    );
+                    if render_button.clicked() {
+                        self.render(self.config.samples_per_pixel);
+                    }
+
+                    let run_button = ui.add_enabled(
+                        !matches!(self.state, AppState::Moving),
+                        egui::Button::new(self.state.to_button_str()),
+                    );
+                    if run_button.clicked() {
+                        match self.state {
+                            AppState::Paused => self.state = AppState::Running,
+                            AppState::Running => self.state = AppState::Paused,
+                            _ => unreachable!(),
+                        }
+                    }
+                });
+            });
+        });
+
+        egui::TopBottomPanel::bottom("status").show(ctx, |ui| {
+            ui.with_layout(egui::Layout::right_to_left(egui::Align::RIGHT), |ui| {
+                ui.label(format!(
+                    "Render time: {:.2}ms",
+                    self.last_render_time.as_micros() as f32 / 1000.0
+                ));
+                ui.label(format!("Samples: {}", self.samples));
+                ui.label(format!("State: {:?}", self.state));
+            })
+        });
+
+        if let Some(error) = &self.save_error {
+            let mut open = true;
+            egui::Window::new("SaveFileError")
+                .anchor(Align2::LEFT_TOP, [SPACING, SPACING])
+                .open(&mut open)
+                .show(ctx, |ui| {
+                    ui.heading("Error");
+                    ui.label(error);
+                });
+            if !open {
+                self.save_error = None;
+            }
+        }
+
+        if world_changed {
+            self.world_snapshot = Arc::new(self.world.clone());
+            scene_changed = true;
+        }
+        if scene_changed {
+            self.pending_reset = true;
+            self.render_thread.cancel();
+        }
+
+        egui::CentralPanel::default().show(ctx, |ui| {
+            // Render image
+            match self.state {
+                AppState::Running => {
+                    if !self.render_thread.is_busy() {
+                        self.render(PREVIEW_SPP);
+                    }
+                }
+                AppState::Moving => {
+                    // Hide cursor if in window
+                    let rect = ctx.available_rect();
+                    ctx.set_cursor_icon(egui::CursorIcon::None);
+                    if ui.rect_contains_pointer(rect.shrink(80.0)) {
+                        let camera = self.camera;
+                        ctx.input(|i| {
+                            self.camera
+                                .move_camera(&camera_input(i), &mut self.skip_mouse_update)
+                        });
+                        if self.camera != camera {
+                            self.pending_reset = true;
+                        }
+                        if ctx.input(|i| {
+                            i.pointer.primary_released() || i.key_released(egui::Key::Escape)
+                        }) {
+                            self.state = AppState::Paused;
+                        }
+                    } else {
+                        let mouse = mouse_rs::Mouse::new();
+                        let point = self.locked_pos.unwrap();
+                        mouse.move_to(point.x, point.y).ok();
+                        self.skip_mouse_update = true;
+                    }
+                    if self.pending_reset || !self.render_thread.is_busy() {
+                        self.render(PREVIEW_SPP);
+                    }
+                }
+                AppState::Paused => {
+                    if ui.rect_contains_pointer(ctx.available_rect()) {
+                        if ctx.input(|i| i.pointer.primary_released()) {
+                            let mouse = mouse_rs::Mouse::new();
+                            let position = mouse.get_position().unwrap();
+                            self.locked_pos = Some(Point {
+                                x: position.x,
+                                y: position.y,
+                            });
+                            self.state = AppState::Moving;
+                            self.skip_mouse_update = false;
+                        }
+                    }
+                }
+            }
+
+            // Display resized image to egui frame
+            let frame = self.resize_to_frame(ui);
+            ui.centered_and_justified(|ui| ui.image(&frame, frame.size_vec2()));
+        });
+
+        // Keep polling for rows while rendering
+        if self.render_thread.is_busy() || self.state != AppState::Paused {
+            ctx.request_repaint();
+        }
+    }
+}
+
+/// Edit a vector in a grid row, returning whether it was changed
+fn vec3_ui(ui: &mut egui::Ui, vector: &mut Vec3) -> bool {
+    let vector: &mut glam::Vec3 = vector;
+    ui.horizontal(|ui| {
+        let mut changed = false;
+        for (value, axis) in [
+            (&mut vector.x, "x"),
+            (&mut vector.y, "y"),
+            (&mut vector.z, "z"),
+        ] {
+            changed |= ui
+                .add(egui::DragValue::new(value).speed(0.01))
+                .on_hover_text(axis)
+                .changed();
+        }
+        changed
+    })
+    .inner
+}
+
+/// Edit a radius or other length, returning whether it was changed
+fn length_ui(ui: &mut egui::Ui, value: &mut f32) -> bool {
+    ui.add(egui::Slider::new(value, 0.0..=100.0).drag_value_speed(0.1))
+        .changed()
+}
+
+/// Edit the properties of a material in grid rows, returning whether it was changed
+fn material_ui(ui: &mut egui::Ui, material: &mut Material) -> bool {
+    let mut changed = false;
+    match material {
+        Material::Lambertian(l) => {
+            ui.label("Albedo");
+            changed |= texture_ui(ui, &mut l.albedo);
+            ui.end_row();
+        }
+        Material::Metal(m) => {
+            ui.label("Albedo");
+            changed |= texture_ui(ui, &mut m.albedo);
+            ui.end_row();
+            ui.label("Fuzz");
+            changed |= ui
+                .add(
+                    egui::DragValue::new(&mut m.fuzz)
+                        .speed(0.01)
+                        .clamp_range(0.0..=1.0),
+                )
+                .changed();
+            ui.end_row();
+        }
+        Material::Dielectric(d) => {
+            ui.label("Refractive index");
+            changed |= ui
+                .add(
+                    egui::DragValue::new(&mut d.refractive_index)
+                        .speed(0.01)
+                        .clamp_range(0.0..=2.0),
+                )
+                .changed();
+            ui.end_row();
+        }
+        Material::Isotropic(i) => {
+            ui.label("Albedo");
+            changed |= texture_ui(ui, &mut i.albedo);
+            ui.end_row();
+        }
+        Material::DiffuseLight(l) => {
+            ui.label("Color");
+            changed |= ui.color_edit_button_rgb(l.color.as_mut()).changed();
+            ui.end_row();
+            ui.label("Intensity");
+            changed |= ui
+                .add(
+                    egui::DragValue::new(&mut l.intensity)
+                        .speed(0.1)
+                        .clamp_range(0.0..=f32::MAX),
+                )
+                .changed();
+            ui.end_row();
+        }
+    }
+    changed
+}
+
+/// Edit the color or wrapping of a texture, returning whether it was changed
+fn texture_ui(ui: &mut egui::Ui, texture: &mut Texture) -> bool {
+    match texture {
+        Texture::Solid(color) => ui.color_edit_button_rgb(color.as_mut()).changed(),
+        Texture::Image(image) => {
+            let mut changed = false;
+            ui.vertical(|ui| {
+                ui.label(image.path.display().to_string());
+                egui::ComboBox::from_id_source(&image.path)
+                    .selected_text(format!("{:?}", image.wrap))
+                    .show_ui(ui, |ui| {
+                        for wrap in [WrapMode::Repeat, WrapMode::MirroredRepeat, WrapMode::Clamp] {
+                            changed |= ui
+                                .selectable_value(&mut image.wrap, wrap, format!("{wrap:?}"))
+                                .changed();
+                        }
+                    });
+            });
+            changed
+        }
+        Texture::Checker(checker) => {
+            ui.horizontal(|ui| {
+                let mut changed = ui.color_edit_button_rgb(checker.even.as_mut()).changed();
+                changed |= ui.color_edit_button_rgb(checker.odd.as_mut()).changed();
+                changed |= ui
+                    .add(
+                        egui::DragValue::new(&mut checker.scale)
+                            .speed(0.01)
+                            .clamp_range(0.001..=f32::MAX),
+                    )
+                    .on_hover_text("Scale")
+                    .changed();
+                changed
+            })
+            .inner
+        }
+        Texture::Noise(noise) => {
+            ui.horizontal(|ui| {
+                let mut changed = ui.color_edit_button_rgb(noise.color.as_mut()).changed();
+                egui::ComboBox::from_id_source(ui.next_auto_id())
+                    .selected_text(format!("{:?}", noise.pattern))
+                    .show_ui(ui, |ui| {
+                        for pattern in [
+                            NoisePattern::Perlin,
+                            NoisePattern::Turbulence,
+                            NoisePattern::Marble,
+                        ] {
+                            changed |= ui
+                                .selectable_value(
+                                    &mut noise.pattern,
+                                    pattern,
+                                    format!("{pattern:?}"),
+                                )
+                                .changed();
+                        }
+                    });
+                changed |= ui
+                    .add(
+                        egui::DragValue::new(&mut noise.scale)
+                            .speed(0.01)
+                            .clamp_range(0.0..=f32::MAX),
+                    )
+                    .on_hover_text("Scale")
+                    .changed();
+                changed
+            })
+            .inner
+        }
+    }
+}
+
+/// Edit the tone mapping and exposure in grid rows, returning whether they were changed
+fn tone_mapping_ui(ui: &mut egui::Ui, config: &mut ImageConfig) -> bool {
+    let mut changed = false;
+
+    ui.label("Tone Mapping");
+    let name = |tone_mapping: &ToneMapping| match tone_mapping {
+        ToneMapping::Clamp => "Clamp",
+        ToneMapping::Reinhard => "Reinhard",
+        ToneMapping::ExtendedReinhard { .. } => "Extended Reinhard",
+        ToneMapping::Aces => "ACES",
+        ToneMapping::AgX => "AgX",
+    };
+    let selected = name(&config.tone_mapping);
+    egui::ComboBox::from_id_source("ToneMapping")
+        .selected_text(selected)
+        .show_ui(ui, |ui| {
+            for tone_mapping in [
+                ToneMapping::Clamp,
+                ToneMapping::Reinhard,
+                ToneMapping::ExtendedReinhard { white: 4.0 },
+                ToneMapping::Aces,
+                ToneMapping::AgX,
+            ] {
+                let option = name(&tone_mapping);
+                if ui.selectable_label(selected == option, option).clicked() && selected != option {
+                    config.tone_mapping = tone_mapping;
+                    changed = true;
+                }
+            }
+        });
+    ui.end_row();
+
+    if let ToneMapping::ExtendedReinhard { white } = &mut config.tone_mapping {
+        ui.label("White Point");
+        changed |= ui
+            .add(egui::Slider::new(white, 1.0..=20.0).logarithmic(true))
+            .changed();
+        ui.end_row();
+    }
+
+    ui.label("Exposure");
+    changed |= ui
+        .add(egui::Slider::new(&mut config.exposure, -10.0..=10.0).suffix(" stops"))
+        .changed();
+    ui.end_row();
+
+    changed
+}
+
+/// Edit the type and properties of the background in grid rows, returning whether it was changed
+fn background_ui(ui: &mut egui::Ui, background: &mut Background) -> bool {
+    let mut changed = false;
+    ui.label("Type");
+    let selected = match background {
+        Background::Solid(_) => "Solid",
+        Background::Gradient { .. } => "Gradient",
+        Background::EnvironmentMap(_) => "Environment map",
+        Background::Off => "Off",
+    };
+    egui::ComboBox::from_id_source("BackgroundType")
+        .selected_text(selected)
+        .show_ui(ui, |ui| {
+            if ui.selectable_label(selected == "Solid", "Solid").clicked() {
+                *background = Background::Solid(Color::new(0.5, 0.5, 0.5));
+                changed = true;
+            }
+            if ui
+                .selectable_label(selected == "Gradient", "Gradient")
+                .clicked()
+            {
+                *background = Background::default();
+                changed = true;
+            }
+            if ui
+                .selectable_label(selected == "Environment map", "Environment map")
+                .clicked()
+            {
+                let dialog = rfd::FileDialog::new()
+                    .set_title("Open environment map")
+                    .add_filter("Images", &["hdr", "exr", "png", "jpg", "jpeg"]);
+                if let Some(path) = dialog.pick_file() {
+                    match EnvironmentMap::new(path) {
+                        Ok(map) => {
+                            *background = Background::EnvironmentMap(map);
+                            changed = true;
+                        }
+                        Err(e) => tracing::error!("Could not load environment map: {e}"),
+                    }
+                }
+            }
+            if ui.selectable_label(selected == "Off", "Off").clicked() {
+                *background = Background::Off;
+                changed = true;
+            }
+        });
+    ui.end_row();
+
+    match background {
+        Background::Solid(color) => {
+            ui.label("Color");
+            changed |= ui.color_edit_button_rgb(color.as_mut()).changed();
+            ui.end_row();
+        }
+        Background::Gradient { bottom, top, up } => {
+            ui.label("Bottom");
+            changed |= ui.color_edit_button_rgb(bottom.as_mut()).changed();
+            ui.end_row();
+            ui.label("Top");
+            changed |= ui.color_edit_button_rgb(top.as_mut()).changed();
+            ui.end_row();
+            ui.label("Up");
+            changed |= vec3_ui(ui, up);
+            ui.end_row();
+        }
+        Background::EnvironmentMap(map) => {
+            ui.label("Image");
+            ui.label(map.path.display().to_string());
+            ui.end_row();
+            ui.label("Rotation");
+            changed |= ui
+                .add(egui::Slider::new(&mut map.rotation, -180.0..=180.0).suffix("°"))
+                .changed();
+            ui.end_row();
+            ui.label("Intensity");
+            changed |= ui
+                .add(
+                    egui::DragValue::new(&mut map.intensity)
+                        .speed(0.01)
+                        .clamp_range(0.0..=f32::MAX),
+                )
+                .changed();
+            ui.end_row();
+        }
+        Background::Off => {}
+    }
+    changed
+}
+
+/// Translate egui's input into camera controls
+fn camera_input(input: &egui::InputState) -> CameraInput {
+    let delta = input.pointer.delta();
+    CameraInput {
+        forward: input.key_pressed(egui::Key::W),
+        backward: input.key_pressed(egui::Key::S),
+        left: input.key_pressed(egui::Key::A),
+        right: input.key_pressed(egui::Key::D),
+        up: input.key_pressed(egui::Key::Space),
+        down: input.modifiers.shift_only(),
+        mouse_delta: [delta.x, delta.y],
+    }
+}
@@ -0,0 +1,138 @@
+use std::{f32::consts::PI, path::PathBuf, sync::Arc};
+
+use image::{ImageError, Rgb32FImage};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    primitive::{Color, Ray, Vec3},
+    texture::{load_linear, sample_bilinear, WrapMode},
+};
+
+fn default_bottom() -> Color {
+    Color::new(1.0, 1.0, 1.0)
+}
+
+fn default_top() -> Color {
+    Color::new(0.5, 0.7, 1.0)
+}
+
+fn default_up() -> Vec3 {
+    Vec3::new(0.0, 1.0, 0.0)
+}
+
+fn default_intensity() -> f32 {
+    1.0
+}
+
+/// What rays that don't hit anything in the world see
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+pub enum Background {
+    /// A single color in every direction
+    Solid(Color),
+    /// A blend between two colors, from looking straight down to looking straight `up`
+    Gradient {
+        #[serde(default = "default_bottom")]
+        bottom: Color,
+        #[serde(default = "default_top")]
+        top: Color,
+        #[serde(default = "default_up")]
+        up: Vec3,
+    },
+    /// An equirectangular image surrounding the scene
+    EnvironmentMap(EnvironmentMap),
+    /// No light at all, so the scene is only lit by emissive materials
+    Off,
+}
+
+impl Default for Background {
+    /// A white to light blue sky
+    fn default() -> Self {
+        Background::Gradient {
+            bottom: default_bottom(),
+            top: default_top(),
+            up: default_up(),
+        }
+    }
+}
+
+impl Background {
+    pub fn color(&self, ray: &Ray) -> Color {
+        match self {
+            Background::Solid(color) => *color,
+            Background::Gradient { bottom, top, up } => {
+                let unit_direction = ray.direction.normalize();
+                let t = 0.5 * (unit_direction.dot(up.normalize_or_zero()) + 1.0);
+                *bottom * (1.0 - t) + *top * t
+            }
+            Background::EnvironmentMap(map) => map.color(ray),
+            Background::Off => Color::ZERO,
+        }
+    }
+}
+
+/// The serialized form of an environment map, which is loaded into an [`EnvironmentMap`]
+#[derive(Deserialize)]
+struct EnvironmentMapData {
+    path: PathBuf,
+    #[serde(default)]
+    rotation: f32,
+    #[serde(default = "default_intensity")]
+    intensity: f32,
+}
+
+impl TryFrom<EnvironmentMapData> for EnvironmentMap {
+    type Error = ImageError;
+
+    fn try_from(data: EnvironmentMapData) -> Result<Self, Self::Error> {
+        let mut map = EnvironmentMap::new(data.path)?;
+        map.rotation = data.rotation;
+        map.intensity = data.intensity;
+        Ok(map)
+    }
+}
+
+/// An equirectangular (latitude-longitude) image, either high dynamic range (e.g. `.hdr` or
+/// `.exr`) or a regular sRGB image.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[serde(try_from = "EnvironmentMapData")]
+pub struct EnvironmentMap {
+    pub path: PathBuf,
+    /// Rotation around the y axis in degrees
+    pub rotation: f32,
+    pub intensity: f32,
+
+    #[serde(skip)]
+    image: Arc<Rgb32FImage>,
+}
+
+impl EnvironmentMap {
+    pub fn new(path: PathBuf) -> Result<Self, ImageError> {
+        let image = load_linear(&path)?;
+        tracing::info!(
+            path = %path.display(),
+            width = image.width(),
+            height = image.height(),
+            "Loaded environment map"
+        );
+
+        Ok(Self {
+            path,
+            rotation: 0.0,
+            intensity: 1.0,
+            image: Arc::new(image),
+        })
+    }
+
+    pub fn color(&self, ray: &Ray) -> Color {
+        let direction = ray.direction.normalize();
+
+        // Longitude and latitude, with u = 0.5 looking down -z
+        let phi = direction.x.atan2(-direction.z) - self.rotation.to_radians();
+        let theta = direction.y.clamp(-1.0, 1.0).acos();
+        let u = (phi / (2.0 * PI) + 0.5).rem_euclid(1.0);
+        let v = theta / PI;
+
+        // Wrap around horizontally, but not over the poles
+        sample_bilinear(&self.image, u, v, [WrapMode::Repeat, WrapMode::Clamp]) * self.intensity
+    }
+}
@@ -1,12 +1,24 @@
-use egui::InputState;
 use glam::Quat;
-use rand::rngs::ThreadRng;
 
 use crate::{
     config::CameraConfig,
-    primitive::{Point, Ray, Vec3},
+    primitive::{self, Point, Ray, Vec3},
+    sampler::Sampler,
 };
 
+/// Camera controls for a single frame, independent of any windowing toolkit
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct CameraInput {
+    pub forward: bool,
+    pub backward: bool,
+    pub left: bool,
+    pub right: bool,
+    pub up: bool,
+    pub down: bool,
+    /// Mouse movement since the last frame, in pixels
+    pub mouse_delta: [f32; 2],
+}
+
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub struct Camera {
     viewport: (f32, f32),
@@ -18,7 +30,7 @@ pub struct Camera {
     pub focus_dist: f32,
 
     pub config: CameraConfig,
-    last_delta: egui::Vec2,
+    last_delta: [f32; 2],
 }
 
 impl Camera {
@@ -33,6 +45,7 @@ impl Camera {
             vertical_fov,
             aperture,
             focus_dist,
+            ..
         } = config;
 
         let viewport = {
@@ -53,24 +66,24 @@ impl Camera {
             aperture,
             focus_dist,
             config,
-            last_delta: egui::vec2(0.0, 0.0),
+            last_delta: [0.0, 0.0],
         }
     }
 
-    pub fn move_camera(&mut self, input: &InputState, skip_mouse_update: &mut bool) {
-        if input.key_pressed(egui::Key::W) {
+    pub fn move_camera(&mut self, input: &CameraInput, skip_mouse_update: &mut bool) {
+        if input.forward {
             self.origin -= self.direction * Camera::MOVE_SPEED;
-        } else if input.key_pressed(egui::Key::S) {
+        } else if input.backward {
             self.origin += self.direction * Camera::MOVE_SPEED;
-        } else if input.key_pressed(egui::Key::A) {
+        } else if input.left {
             let right: Vec3 = self.direction.cross(*Camera::UP).into();
             self.origin += right * Camera::MOVE_SPEED;
-        } else if input.key_pressed(egui::Key::D) {
+        } else if input.right {
             let right: Vec3 = self.direction.cross(*Camera::UP).into();
             self.origin -= right * Camera::MOVE_SPEED;
-        } else if input.key_pressed(egui::Key::Space) {
+        } else if input.up {
             self.origin += Camera::UP * Camera::MOVE_SPEED;
-        } else if input.modifiers.shift_only() {
+        } else if input.down {
             self.origin -= Camera::UP * Camera::MOVE_SPEED;
         }
 
@@ -78,25 +91,25 @@ impl Camera {
             *skip_mouse_update = false;
             self.last_delta
         } else {
-            let delta = input.pointer.delta();
-            self.last_delta = delta;
-            delta
+            self.last_delta = input.mouse_delta;
+            input.mouse_delta
         };
-        if delta.x != 0.0 || delta.y != 0.0 {
+        let [dx, dy] = delta;
+        if dx != 0.0 || dy != 0.0 {
             let right = self.direction.cross(*Camera::UP);
-            let pitch = Quat::from_axis_angle(right, delta.y * Camera::ROTATE_SPEED);
-            let yaw = Quat::from_axis_angle(*Camera::UP, -delta.x * Camera::ROTATE_SPEED);
+            let pitch = Quat::from_axis_angle(right, dy * Camera::ROTATE_SPEED);
+            let yaw = Quat::from_axis_angle(*Camera::UP, -dx * Camera::ROTATE_SPEED);
 
             let rotation = pitch.mul_quat(yaw).normalize();
             self.direction = rotation.mul_vec3(*self.direction).into();
         }
     }
 
-    pub fn get_ray(&self, rng: &mut ThreadRng, s: f32, t: f32) -> Ray {
+    pub fn get_ray(&self, sampler: &mut dyn Sampler, s: f32, t: f32) -> Ray {
         let u = Camera::UP.cross(*self.direction).normalize();
         let v = self.direction.cross(u);
         let offset = {
-            let rd = Vec3::new_random_in_unit_disk(rng) * self.aperture / 2.0;
+            let rd = primitive::sample_concentric_disk(sampler.get_2d()) * self.aperture / 2.0;
             (u * rd.x + v * rd.y).into()
         };
 
@@ -106,9 +119,17 @@ impl Camera {
         let lower_left =
             self.origin - horizontal / 2.0 - vertical / 2.0 - self.direction * self.focus_dist;
 
+        let CameraConfig {
+            shutter_open,
+            shutter_close,
+            ..
+        } = self.config;
+        let time = shutter_open + (shutter_close - shutter_open) * sampler.get_1d();
+
         Ray::new(
             self.origin + offset,
             lower_left + horizontal * s + vertical * t - self.origin - offset,
+            time,
         )
     }
 }
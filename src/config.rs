@@ -1,8 +1,32 @@
-use std::num::NonZeroU32;
+use std::{collections::HashMap, num::NonZeroU32, path::Path, sync::Arc};
 
 use serde::{Deserialize, Serialize};
+use thiserror::Error;
 
-use crate::{object::Object, primitive::Point};
+use crate::{
+    background::Background, object::Object, primitive::Point, sampler::SamplerType,
+    tone_mapping::ToneMapping,
+};
+
+#[derive(Error, Debug)]
+pub enum ConfigError {
+    #[error("could not read config: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("invalid config: {0}")]
+    Toml(#[from] toml::de::Error),
+    #[error("could not resolve config paths: {0}")]
+    Resolve(#[from] toml::ser::Error),
+    #[error("unknown prototype `{0}`")]
+    UnknownPrototype(String),
+}
+
+fn default_roulette_depth() -> i32 {
+    3
+}
+
+fn default_roulette_probability() -> f32 {
+    0.05
+}
 
 #[derive(Deserialize, Serialize, Debug, Clone, Copy)]
 pub struct ImageConfig {
@@ -10,6 +34,24 @@ pub struct ImageConfig {
     pub height: NonZeroU32,
     pub samples_per_pixel: usize,
     pub max_ray_depth: i32,
+    /// Bounces before paths can be terminated early with Russian roulette
+    #[serde(default = "default_roulette_depth")]
+    pub roulette_depth: i32,
+    /// The lowest chance of terminating a path at each bounce after `roulette_depth`, which is
+    /// higher for paths that carry little light
+    #[serde(default = "default_roulette_probability")]
+    pub roulette_probability: f32,
+    /// Seed for all random sampling, renders with the same seed are identical
+    #[serde(default)]
+    pub seed: u64,
+    /// How the random numbers for each sample are generated
+    #[serde(default)]
+    pub sampler: SamplerType,
+    #[serde(default)]
+    pub tone_mapping: ToneMapping,
+    /// Exposure adjustment in stops, applied before tone mapping
+    #[serde(default)]
+    pub exposure: f32,
 }
 
 #[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq)]
@@ -19,11 +61,166 @@ pub struct CameraConfig {
     pub vertical_fov: f32,
     pub aperture: f32,
     pub focus_dist: Option<f32>,
+    /// When the shutter opens, moving objects are at their start at time 0 and their end at 1
+    #[serde(default)]
+    pub shutter_open: f32,
+    /// When the shutter closes, leave it at `shutter_open` to disable motion blur
+    #[serde(default)]
+    pub shutter_close: f32,
 }
 
 #[derive(Deserialize, Serialize, Debug)]
 pub struct Config {
     pub camera: CameraConfig,
     pub image: ImageConfig,
+    #[serde(default)]
+    pub background: Background,
     pub world: Vec<Object>,
+    /// Objects that are only rendered through an `Instance` referring to them by name. Prototypes
+    /// can't refer to other prototypes.
+    #[serde(default)]
+    pub prototypes: HashMap<String, Arc<Object>>,
+}
+
+impl Config {
+    /// Load a config file, resolving every relative `path` in it relative to the config file.
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, ConfigError> {
+        let path = path.as_ref();
+        let mut value: toml::Value = toml::from_str(&std::fs::read_to_string(path)?)?;
+
+        if let Some(directory) = path.parent() {
+            resolve_paths(&mut value, directory);
+        }
+        // `Value::try_into` can't deserialize externally tagged enums like `Object`, so go back
+        // through a string
+        let mut config: Config = toml::from_str(&toml::to_string(&value)?)?;
+        config.link_prototypes()?;
+        Ok(config)
+    }
+
+    /// Point every instance of a prototype in the world to the prototype, which is shared between
+    /// all of its instances
+    fn link_prototypes(&mut self) -> Result<(), ConfigError> {
+        for prototype in self.prototypes.values_mut() {
+            if let Some(object) = Arc::get_mut(prototype) {
+                link_prototypes(object, &HashMap::new())?;
+            }
+        }
+        for object in self.world.iter_mut() {
+            link_prototypes(object, &self.prototypes)?;
+        }
+        Ok(())
+    }
+}
+
+fn link_prototypes(
+    object: &mut Object,
+    prototypes: &HashMap<String, Arc<Object>>,
+) -> Result<(), ConfigError> {
+    if let Object::Instance(instance) = object {
+        if let Some(name) = &instance.prototype {
+            let prototype = prototypes
+                .get(name)
+                .ok_or_else(|| ConfigError::UnknownPrototype(name.clone()))?;
+            instance.link(Arc::clone(prototype));
+        } else if let Some(object) = instance.object_mut() {
+            link_prototypes(object, prototypes)?;
+        }
+    }
+    Ok(())
+}
+
+fn resolve_paths(value: &mut toml::Value, directory: &Path) {
+    match value {
+        toml::Value::Table(table) => {
+            for (key, value) in table.iter_mut() {
+                match value {
+                    toml::Value::String(path) if key == "path" => {
+                        if Path::new(path).is_relative() {
+                            *path = directory.join(&path).to_string_lossy().into_owned();
+                        }
+                    }
+                    _ => resolve_paths(value, directory),
+                }
+            }
+        }
+        toml::Value::Array(array) => {
+            for value in array.iter_mut() {
+                resolve_paths(value, directory);
+            }
+        }
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        material::{Lambertian, Material},
+        texture::Texture,
+    };
+
+    const CONFIG: &str = r#"
+[camera]
+look_from = [0, 0, 5]
+look_to = [0, 0, 0]
+vertical_fov = 40
+aperture = 0
+
+[image]
+width = 16
+height = 16
+samples_per_pixel = 1
+max_ray_depth = 4
+
+[prototypes.quad]
+Mesh.path = "meshes/quad.obj"
+Mesh.material.Lambertian.albedo = [0.5, 0.5, 0.5]
+
+[[world]]
+Mesh.path = "meshes/quad.obj"
+Mesh.material.Lambertian.albedo.Image.path = "textures/checker.png"
+
+[[world]]
+Instance.prototype = "quad"
+Instance.translate = [0, 2, 0]
+"#;
+
+    #[test]
+    fn paths_are_relative_to_the_config() {
+        let directory = std::env::temp_dir().join("raytracing-config-paths");
+        let _ = std::fs::remove_dir_all(&directory);
+        std::fs::create_dir_all(directory.join("meshes")).unwrap();
+        std::fs::create_dir_all(directory.join("textures")).unwrap();
+        std::fs::write(directory.join("scene.toml"), CONFIG).unwrap();
+        std::fs::write(
+            directory.join("meshes/quad.obj"),
+            "v 0 0 0\nv 1 0 0\nv 1 1 0\nv 0 1 0\nvt 0 0\nf 1/1 2/1 3/1 4/1\n",
+        )
+        .unwrap();
+        image::RgbImage::new(2, 2)
+            .save(directory.join("textures/checker.png"))
+            .unwrap();
+
+        let config = Config::from_file(directory.join("scene.toml")).unwrap();
+
+        let [Object::Mesh(mesh), Object::Instance(instance)] = &config.world[..] else {
+            panic!("unexpected world {:?}", config.world);
+        };
+        assert_eq!(mesh.triangle_count(), 2);
+        let Material::Lambertian(Lambertian {
+            albedo: Texture::Image(image),
+        }) = &mesh.materials[0]
+        else {
+            panic!("unexpected material {:?}", mesh.materials[0]);
+        };
+        assert_eq!(image.path, directory.join("textures/checker.png"));
+
+        let Some(object) = instance.object() else {
+            panic!("the prototype wasn't linked");
+        };
+        assert!(Arc::ptr_eq(object, &config.prototypes["quad"]));
+        assert!(matches!(&**object, Object::Mesh(mesh) if mesh.triangle_count() == 2));
+    }
 }
@@ -1,35 +1,76 @@
+use std::num::NonZeroU32;
+
 use clap::{arg, value_parser, Command};
 use color_eyre::Result;
 
-use raytracing::{App, Config};
+use raytracing::{sampler::SamplerType, Config};
 
 fn main() -> Result<()> {
     setup()?;
     tracing::debug!("Debug logging enabled.");
 
-    let config: Config = {
-        let matches = Command::new("raytracer")
-            .arg(
-                arg!(--config <config> "A scene configuration.")
-                    .value_parser(value_parser!(std::path::PathBuf)),
-            )
-            .arg(
-                arg!(--scene <scene> "A builtin scene.")
-                    .value_parser(clap::builder::PossibleValuesParser::new(["rtiow_final"])),
-            )
-            .group(
-                clap::ArgGroup::new("scenes")
-                    .args(["config", "scene"])
-                    .required(true),
-            )
-            .get_matches();
+    let matches = Command::new("raytracer")
+        .arg(
+            arg!(--config <config> "A scene configuration.")
+                .value_parser(value_parser!(std::path::PathBuf)),
+        )
+        .arg(arg!(--scene <scene> "A builtin scene.").value_parser(
+            clap::builder::PossibleValuesParser::new([
+                "rtiow_final",
+                "cornell_box",
+                "cornell_smoke",
+            ]),
+        ))
+        .group(
+            clap::ArgGroup::new("scenes")
+                .args(["config", "scene"])
+                .required(true),
+        )
+        .arg(
+            arg!(--output <output> "Render without a window and save the image to this path.")
+                .value_parser(value_parser!(std::path::PathBuf)),
+        )
+        .arg(
+            arg!(--width <width> "Override the image width.")
+                .value_parser(value_parser!(u32).range(1..)),
+        )
+        .arg(
+            arg!(--height <height> "Override the image height.")
+                .value_parser(value_parser!(u32).range(1..)),
+        )
+        .arg(
+            arg!(--spp <spp> "Override the samples per pixel.")
+                .value_parser(value_parser!(u64).range(1..)),
+        )
+        .arg(
+            arg!(--seed <seed> "Override the random seed, also used to generate builtin scenes.")
+                .value_parser(value_parser!(u64)),
+        )
+        .arg(
+            arg!(--depth <depth> "Override the maximum ray depth.")
+                .value_parser(value_parser!(i32).range(1..)),
+        )
+        .arg(
+            arg!(--sampler <sampler> "Override the sampler.").value_parser(
+                clap::builder::PossibleValuesParser::new([
+                    "independent",
+                    "stratified",
+                    "halton",
+                    "sobol",
+                ]),
+            ),
+        )
+        .get_matches();
 
+    let seed = matches.get_one::<u64>("seed").copied();
+    let mut config: Config = {
         if let Some(config) = matches.get_one::<std::path::PathBuf>("config") {
-            let config = std::fs::read_to_string(config)?;
-            toml::from_str(&config)?
+            Config::from_file(config)?
         } else if let Some(config) = matches.get_one::<String>("scene") {
             match config.as_str() {
-                "rtiow_final" => raytracing::scenes::rtiow::final_scene(),
+                "rtiow_final" => raytracing::scenes::rtiow::final_scene(seed.unwrap_or_default()),
+                "cornell_box" => raytracing::scenes::rtnw::cornell_box(),
+                "cornell_smoke" => raytracing::scenes::rtnw::cornell_smoke(),
                 _ => unreachable!(),
             }
         } else {
@@ -37,17 +78,59 @@ fn main() -> Result<()> {
         }
     };
 
+    if let Some(&width) = matches.get_one::<u32>("width") {
+        config.image.width = NonZeroU32::new(width).unwrap();
+    }
+    if let Some(&height) = matches.get_one::<u32>("height") {
+        config.image.height = NonZeroU32::new(height).unwrap();
+    }
+    if let Some(&spp) = matches.get_one::<u64>("spp") {
+        config.image.samples_per_pixel = spp as usize;
+    }
+    if let Some(&depth) = matches.get_one::<i32>("depth") {
+        config.image.max_ray_depth = depth;
+    }
+    if let Some(seed) = seed {
+        config.image.seed = seed;
+    }
+    if let Some(sampler) = matches.get_one::<String>("sampler") {
+        config.image.sampler = match sampler.as_str() {
+            "independent" => SamplerType::Independent,
+            "stratified" => SamplerType::Stratified,
+            "halton" => SamplerType::Halton,
+            "sobol" => SamplerType::Sobol,
+            _ => unreachable!(),
+        };
+    }
+
+    if let Some(output) = matches.get_one::<std::path::PathBuf>("output") {
+        raytracing::render_to_file(config, output)?;
+        return Ok(());
+    }
+
+    run_gui(config)
+}
+
+#[cfg(feature = "gui")]
+fn run_gui(config: Config) -> Result<()> {
     let options = eframe::NativeOptions::default();
     eframe::run_native(
         "raytracing",
         options,
-        Box::new(|_cc| Box::new(App::new(config))),
+        Box::new(|_cc| Box::new(raytracing::App::new(config))),
     )
     .unwrap();
 
     Ok(())
 }
 
+#[cfg(not(feature = "gui"))]
+fn run_gui(_config: Config) -> Result<()> {
+    Err(color_eyre::eyre::eyre!(
+        "built without the `gui` feature, use --output to render to a file"
+    ))
+}
+
 fn setup() -> Result<()> {
     use tracing_subscriber::{fmt, prelude::*, EnvFilter};
 
@@ -1,9 +1,9 @@
-use rand::{rngs::ThreadRng, Rng};
 use serde::{Deserialize, Serialize};
 
 use crate::{
     object::HitRecord,
     primitive::{Color, Ray, Vec3},
+    sampler::Sampler,
 };
 
 use super::ScatterResult;
@@ -22,7 +22,7 @@ pub struct Dielectric {
 
 pub fn scatter(
     material: &Dielectric,
-    rng: &mut ThreadRng,
+    sampler: &mut dyn Sampler,
     r_in: &Ray,
     record: &HitRecord,
 ) -> Option<ScatterResult> {
@@ -38,7 +38,7 @@ pub fn scatter(
     let sin_theta = (1.0 - cos_theta * cos_theta).sqrt();
 
     let cannot_refract = (refraction_ratio * sin_theta) > 1.0;
-    let should_reflect = reflectance(cos_theta, refraction_ratio) > rng.gen::<f32>();
+    let should_reflect = reflectance(cos_theta, refraction_ratio) > sampler.get_1d();
 
     // Cannot refract
     let direction = if cannot_refract || should_reflect {
@@ -48,7 +48,8 @@ pub fn scatter(
     };
 
     Some(ScatterResult {
-        ray: Ray::new(record.point, direction),
+        ray: Ray::new(record.point, direction, r_in.time),
         attenuation: Color::new(1.0, 1.0, 1.0),
+        pdf: None,
     })
 }
@@ -0,0 +1,19 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{object::HitRecord, primitive::Color};
+
+fn default_intensity() -> f32 {
+    1.0
+}
+
+/// A material that emits light evenly in every direction and doesn't scatter incoming rays
+#[derive(Debug, PartialEq, Clone, Copy, Deserialize, Serialize)]
+pub struct DiffuseLight {
+    pub color: Color,
+    #[serde(default = "default_intensity")]
+    pub intensity: f32,
+}
+
+pub fn emitted(material: &DiffuseLight, _: &HitRecord) -> Color {
+    material.color * material.intensity
+}
@@ -0,0 +1,44 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    object::HitRecord,
+    primitive::{self, Color, Ray, Vec3},
+    sampler::Sampler,
+    texture::{self, Texture},
+};
+
+use super::ScatterResult;
+
+/// A material that scatters light evenly in every direction, regardless of the surface normal. Used
+/// as the phase function of participating media.
+#[derive(Debug, PartialEq, Clone, Deserialize, Serialize)]
+pub struct Isotropic {
+    #[serde(deserialize_with = "texture::deserialize")]
+    pub albedo: Texture,
+}
+
+pub fn scatter(
+    material: &Isotropic,
+    sampler: &mut dyn Sampler,
+    r_in: &Ray,
+    record: &HitRecord,
+) -> Option<ScatterResult> {
+    Some(ScatterResult {
+        ray: Ray::new(
+            record.point,
+            primitive::sample_uniform_sphere(sampler.get_2d()),
+            r_in.time,
+        ),
+        attenuation: material.albedo.value(record.uv, &record.point),
+        pdf: Some(pdf(r_in, record, Vec3::ZERO)),
+    })
+}
+
+pub fn eval(material: &Isotropic, r_in: &Ray, record: &HitRecord, direction: Vec3) -> Color {
+    material.albedo.value(record.uv, &record.point) * pdf(r_in, record, direction)
+}
+
+/// Every direction is equally likely
+pub fn pdf(_: &Ray, _: &HitRecord, _: Vec3) -> f32 {
+    1.0 / (4.0 * std::f32::consts::PI)
+}
@@ -1,31 +1,41 @@
-use rand::rngs::ThreadRng;
 use serde::{Deserialize, Serialize};
 
 use crate::{
     object::HitRecord,
-    primitive::{Color, Ray, Vec3},
+    primitive::{self, Color, Onb, Ray, Vec3},
+    sampler::Sampler,
+    texture::{self, Texture},
 };
 
 use super::ScatterResult;
 
-#[derive(Debug, PartialEq, Clone, Copy, Deserialize, Serialize)]
+#[derive(Debug, PartialEq, Clone, Deserialize, Serialize)]
 pub struct Lambertian {
-    pub albedo: Color,
+    #[serde(deserialize_with = "texture::deserialize")]
+    pub albedo: Texture,
 }
 
 pub fn scatter(
     material: &Lambertian,
-    rng: &mut ThreadRng,
-    _: &Ray,
+    sampler: &mut dyn Sampler,
+    r_in: &Ray,
     record: &HitRecord,
 ) -> Option<ScatterResult> {
-    let mut scatter_direction = record.normal + Vec3::new_random_unit_vector(rng);
-    if scatter_direction.is_near_zero() {
-        scatter_direction = record.normal;
-    }
+    let scatter_direction =
+        Onb::new(record.normal).to_world(primitive::sample_cosine_hemisphere(sampler.get_2d()));
 
     Some(ScatterResult {
-        ray: Ray::new(record.point, scatter_direction),
-        attenuation: material.albedo,
+        ray: Ray::new(record.point, scatter_direction, r_in.time),
+        attenuation: material.albedo.value(record.uv, &record.point),
+        pdf: Some(pdf(r_in, record, scatter_direction)),
     })
 }
+
+pub fn eval(material: &Lambertian, r_in: &Ray, record: &HitRecord, direction: Vec3) -> Color {
+    material.albedo.value(record.uv, &record.point) * pdf(r_in, record, direction)
+}
+
+/// Directions are sampled proportionally to their cosine to the normal
+pub fn pdf(_: &Ray, record: &HitRecord, direction: Vec3) -> f32 {
+    record.normal.dot(direction.normalize()).max(0.0) / std::f32::consts::PI
+}
@@ -1,37 +1,77 @@
-use rand::rngs::ThreadRng;
 use serde::{Deserialize, Serialize};
 
 use crate::{
     object::HitRecord,
-    primitive::{Color, Ray, Vec3},
+    primitive::{self, Color, Ray, Vec3},
+    sampler::Sampler,
+    texture::{self, Texture},
 };
 
 use super::ScatterResult;
 
-#[derive(Debug, PartialEq, Clone, Copy, Deserialize, Serialize)]
+#[derive(Debug, PartialEq, Clone, Deserialize, Serialize)]
 pub struct Metal {
-    pub albedo: Color,
+    #[serde(deserialize_with = "texture::deserialize")]
+    pub albedo: Texture,
     pub fuzz: f32,
 }
 
 pub fn scatter(
     material: &Metal,
-    rng: &mut ThreadRng,
+    sampler: &mut dyn Sampler,
     r_in: &Ray,
     record: &HitRecord,
 ) -> Option<ScatterResult> {
     let reflected = Vec3::from(r_in.direction.normalize()).reflect(&record.normal);
     let scattered = Ray::new(
         record.point,
-        Vec3::new_random_in_unit_sphere(rng) * material.fuzz + reflected.into(),
+        primitive::sample_uniform_ball([sampler.get_1d(), sampler.get_1d(), sampler.get_1d()])
+            * material.fuzz
+            + reflected,
+        r_in.time,
     );
 
     if scattered.direction.dot(*record.normal) > 0. {
         Some(ScatterResult {
             ray: scattered,
-            attenuation: material.albedo,
+            attenuation: material.albedo.value(record.uv, &record.point),
+            pdf: (material.fuzz > 0.0).then(|| pdf(material, r_in, record, scattered.direction)),
         })
     } else {
         None
     }
 }
+
+pub fn eval(material: &Metal, r_in: &Ray, record: &HitRecord, direction: Vec3) -> Color {
+    if direction.dot(*record.normal) <= 0.0 {
+        return Color::ZERO;
+    }
+    material.albedo.value(record.uv, &record.point) * pdf(material, r_in, record, direction)
+}
+
+/// The reflection is fuzzed by a point uniformly in a ball of radius `fuzz`, so the density of a
+/// direction is the volume of the ball seen along it, weighted by the distance squared
+pub fn pdf(material: &Metal, r_in: &Ray, record: &HitRecord, direction: Vec3) -> f32 {
+    let fuzz = material.fuzz;
+    if fuzz <= 0.0 {
+        return 0.0;
+    }
+    let reflected = *Vec3::from(r_in.direction.normalize()).reflect(&record.normal);
+    let direction = direction.normalize();
+
+    // The ray t * direction is inside the ball between t = c ± s
+    let c = direction.dot(reflected);
+    let s_squared = fuzz * fuzz - direction.cross(reflected).length_squared();
+    if s_squared <= 0.0 {
+        return 0.0;
+    }
+    let s = s_squared.sqrt();
+    let (near, far) = ((c - s).max(0.0), c + s);
+    if far <= 0.0 {
+        return 0.0;
+    }
+
+    // ∫ t² dt over the chord, with a uniform density of 3 / 4πf³ in the ball
+    let chord = (far - near) * (far * far + far * near + near * near);
+    chord / (4.0 * std::f32::consts::PI * fuzz * fuzz * fuzz)
+}
@@ -1,43 +1,93 @@
 use derive_more::From;
-use rand::rngs::ThreadRng;
 use serde::{Deserialize, Serialize};
 
 use crate::{
     object::HitRecord,
-    primitive::{Color, Ray},
+    primitive::{Color, Ray, Vec3},
+    sampler::Sampler,
 };
 
 mod dielectric;
+mod diffuse_light;
+mod isotropic;
 mod lambertian;
 mod metal;
 
 pub use dielectric::Dielectric;
+pub use diffuse_light::DiffuseLight;
+pub use isotropic::Isotropic;
 pub use lambertian::Lambertian;
 pub use metal::Metal;
 
+/// A ray scattered off a surface, and how much of its light gets through
 pub struct ScatterResult {
+    /// The BSDF times the cosine term, divided by `pdf`
     pub attenuation: Color,
     pub ray: Ray,
+    /// The probability density of sampling the direction of `ray` per unit solid angle, or `None`
+    /// for specular scattering that can only go in specific directions
+    pub pdf: Option<f32>,
 }
 
-#[derive(Debug, PartialEq, Clone, Copy, Deserialize, Serialize, From)]
+/// How a surface scatters and emits light
+#[derive(Debug, PartialEq, Clone, Deserialize, Serialize, From)]
 pub enum Material {
     Lambertian(Lambertian),
     Metal(Metal),
     Dielectric(Dielectric),
+    DiffuseLight(DiffuseLight),
+    Isotropic(Isotropic),
 }
 
 impl Material {
     pub fn scatter(
         &self,
-        rng: &mut ThreadRng,
+        sampler: &mut dyn Sampler,
         r_in: &Ray,
         record: &HitRecord,
     ) -> Option<ScatterResult> {
         match self {
-            Material::Lambertian(l) => lambertian::scatter(l, rng, r_in, record),
-            Material::Metal(m) => metal::scatter(m, rng, r_in, record),
-            Material::Dielectric(d) => dielectric::scatter(d, rng, r_in, record),
+            Material::Lambertian(l) => lambertian::scatter(l, sampler, r_in, record),
+            Material::Metal(m) => metal::scatter(m, sampler, r_in, record),
+            Material::Dielectric(d) => dielectric::scatter(d, sampler, r_in, record),
+            Material::DiffuseLight(_) => None,
+            Material::Isotropic(i) => isotropic::scatter(i, sampler, r_in, record),
+        }
+    }
+
+    /// Whether the material emits any light
+    pub fn is_emissive(&self) -> bool {
+        matches!(self, Material::DiffuseLight(_))
+    }
+
+    /// The BSDF times the cosine term for light arriving from `direction` and leaving along
+    /// `r_in` reversed. Zero for perfectly specular materials, which only
+    /// [`Material::scatter`] can sample.
+    pub fn eval(&self, r_in: &Ray, record: &HitRecord, direction: Vec3) -> Color {
+        match self {
+            Material::Lambertian(l) => lambertian::eval(l, r_in, record, direction),
+            Material::Metal(m) => metal::eval(m, r_in, record, direction),
+            Material::Isotropic(i) => isotropic::eval(i, r_in, record, direction),
+            _ => Color::ZERO,
+        }
+    }
+
+    /// The probability density of [`Material::scatter`] sampling `direction`, per unit solid
+    /// angle. Zero for specular materials.
+    pub fn pdf(&self, r_in: &Ray, record: &HitRecord, direction: Vec3) -> f32 {
+        match self {
+            Material::Lambertian(_) => lambertian::pdf(r_in, record, direction),
+            Material::Metal(m) => metal::pdf(m, r_in, record, direction),
+            Material::Isotropic(_) => isotropic::pdf(r_in, record, direction),
+            _ => 0.0,
+        }
+    }
+
+    /// The light emitted by the material at the hit point
+    pub fn emitted(&self, record: &HitRecord) -> Color {
+        match self {
+            Material::DiffuseLight(l) => diffuse_light::emitted(l, record),
+            _ => Color::ZERO,
         }
     }
 }
@@ -1,8 +1,4 @@
-use crate::{
-    material::Material,
-    object::{HitRecord, Hittable},
-    primitive::{Point, Ray, Vec3},
-};
+use crate::primitive::{Point, Ray};
 use serde::{Deserialize, Serialize};
 
 macro_rules! zip {
@@ -13,27 +9,134 @@ macro_rules! zip {
     )
 }
 
-#[derive(Debug, PartialEq, Deserialize, Serialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, Serialize)]
 pub struct Aabb {
-    minimum: Point,
-    maximum: Point,
+    pub minimum: Point,
+    pub maximum: Point,
 }
 
 impl Aabb {
+    /// A box containing nothing, useful as the starting point when growing a box.
+    pub const EMPTY: Aabb = Aabb {
+        minimum: Point::new(f32::INFINITY, f32::INFINITY, f32::INFINITY),
+        maximum: Point::new(f32::NEG_INFINITY, f32::NEG_INFINITY, f32::NEG_INFINITY),
+    };
+
     pub fn new(minimum: Point, maximum: Point) -> Self {
         Self { minimum, maximum }
     }
 
+    /// The smallest box containing both `self` and `other`
+    pub fn surrounding(&self, other: &Aabb) -> Self {
+        Self {
+            minimum: self.minimum.min(*other.minimum).into(),
+            maximum: self.maximum.max(*other.maximum).into(),
+        }
+    }
+
+    /// The smallest box containing both `self` and `point`
+    pub fn grow(&self, point: Point) -> Self {
+        Self {
+            minimum: self.minimum.min(*point).into(),
+            maximum: self.maximum.max(*point).into(),
+        }
+    }
+
+    /// Widen every axis that is thinner than `delta` to `delta`, centered on the original box
+    pub fn pad(&self, delta: f32) -> Self {
+        let half = (glam::Vec3::splat(delta) - self.extent()).max(glam::Vec3::ZERO) * 0.5;
+        Self {
+            minimum: (*self.minimum - half).into(),
+            maximum: (*self.maximum + half).into(),
+        }
+    }
+
+    pub fn centroid(&self) -> Point {
+        (self.minimum + self.maximum) * 0.5
+    }
+
+    pub fn extent(&self) -> glam::Vec3 {
+        *self.maximum - *self.minimum
+    }
+
+    pub fn surface_area(&self) -> f32 {
+        let e = self.extent();
+        if e.min_element() < 0.0 {
+            return 0.0;
+        }
+        2.0 * (e.x * e.y + e.y * e.z + e.z * e.x)
+    }
+
     #[inline]
-    pub fn hit(&self, ray: &Ray, t_min: f32, t_max: f32) -> bool {
-        for (origin, (direction, (_min, _max))) in zip!(
+    pub fn hit(&self, ray: &Ray, mut t_min: f32, mut t_max: f32) -> bool {
+        for (&origin, (&direction, (&min, max))) in zip!(
             ray.origin.to_array(),
             ray.direction.to_array(),
             self.minimum.to_array(),
             self.maximum.to_array()
         ) {
+            // Slab test, see Andrew Kensler's optimized version in "Ray Tracing: The Next Week"
             let inv_d = 1.0 / direction;
+            let mut t0 = (min - origin) * inv_d;
+            let mut t1 = (max - origin) * inv_d;
+            if inv_d < 0.0 {
+                std::mem::swap(&mut t0, &mut t1);
+            }
+
+            // `f32::max`/`f32::min` ignore NaNs, which occur when the ray lies on a slab
+            t_min = t0.max(t_min);
+            t_max = t1.min(t_max);
+            if t_max <= t_min {
+                return false;
+            }
         }
-        return true;
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::primitive::Vec3;
+
+    fn unit_box() -> Aabb {
+        Aabb::new(Point::new(0.0, 0.0, 0.0), Point::new(1.0, 1.0, 1.0))
+    }
+
+    fn ray(origin: [f32; 3], direction: [f32; 3]) -> Ray {
+        let [x, y, z] = origin;
+        let [dx, dy, dz] = direction;
+        Ray::new(Point::new(x, y, z), Vec3::new(dx, dy, dz), 0.0)
+    }
+
+    #[test]
+    fn axis_parallel_rays() {
+        // Zero direction components make the inverse infinite, for either sign of zero
+        assert!(unit_box().hit(&ray([0.5, 0.5, -5.0], [0.0, 0.0, 1.0]), 0.0, f32::MAX));
+        assert!(unit_box().hit(&ray([0.5, 0.5, 5.0], [-0.0, 0.0, -1.0]), 0.0, f32::MAX));
+        assert!(unit_box().hit(&ray([-5.0, 0.5, 0.5], [1.0, 0.0, -0.0]), 0.0, f32::MAX));
+        assert!(!unit_box().hit(&ray([2.0, 0.5, -5.0], [0.0, 0.0, 1.0]), 0.0, f32::MAX));
+        assert!(!unit_box().hit(&ray([0.5, -0.5, -5.0], [-0.0, 0.0, 1.0]), 0.0, f32::MAX));
+    }
+
+    #[test]
+    fn ray_on_a_face() {
+        // The slab test gives NaN here, which is ignored rather than rejecting the box
+        assert!(unit_box().hit(&ray([0.5, 1.0, -5.0], [0.0, 0.0, 1.0]), 0.0, f32::MAX));
+    }
+
+    #[test]
+    fn ray_starting_inside() {
+        assert!(unit_box().hit(&ray([0.5, 0.5, 0.5], [1.0, 2.0, 3.0]), 0.0, f32::MAX));
+        assert!(unit_box().hit(&ray([0.5, 0.5, 0.5], [-1.0, 0.0, 0.0]), 0.0, f32::MAX));
+    }
+
+    #[test]
+    fn misses() {
+        assert!(!unit_box().hit(&ray([5.0, 5.0, 5.0], [1.0, 0.0, 0.0]), 0.0, f32::MAX));
+        assert!(!unit_box().hit(&ray([-1.0, -1.0, 2.0], [1.0, 1.0, 0.0]), 0.0, f32::MAX));
+        // Behind the ray, or beyond `t_max`
+        assert!(!unit_box().hit(&ray([0.5, 0.5, 3.0], [0.0, 0.0, 1.0]), 0.0, f32::MAX));
+        assert!(!unit_box().hit(&ray([0.5, 0.5, -5.0], [0.0, 0.0, 1.0]), 0.0, 4.0));
     }
 }
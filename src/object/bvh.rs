@@ -0,0 +1,277 @@
+use crate::{
+    object::{aabb::Aabb, HitRecord},
+    primitive::Ray,
+};
+
+/// Number of buckets primitives are binned into when evaluating split candidates
+const SAH_BUCKETS: usize = 12;
+/// Nodes with this many primitives or fewer are never split
+const MAX_LEAF_SIZE: usize = 4;
+/// Maximum depth of the hierarchy, which bounds the size of the traversal stack
+const MAX_DEPTH: usize = 64;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Node {
+    bbox: Aabb,
+    /// Index of the left child for interior nodes, or of the first primitive for leaves.
+    /// The right child of an interior node is always stored at `first + 1`.
+    first: usize,
+    /// Number of primitives in a leaf, zero for interior nodes
+    count: usize,
+}
+
+/// A bounding volume hierarchy over a list of primitives, built with the surface area heuristic.
+///
+/// The hierarchy only stores indices into the primitive list it was built from, so it can be used
+/// for anything that has a bounding box (objects in the world, triangles in a mesh, ...).
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Bvh {
+    nodes: Vec<Node>,
+    indices: Vec<usize>,
+}
+
+impl Bvh {
+    pub fn new(boxes: &[Aabb]) -> Self {
+        let mut bvh = Self {
+            nodes: Vec::with_capacity(boxes.len().max(1) * 2),
+            indices: (0..boxes.len()).collect(),
+        };
+        if boxes.is_empty() {
+            return bvh;
+        }
+
+        let centroids: Vec<_> = boxes.iter().map(|b| *b.centroid()).collect();
+        bvh.nodes.push(Node {
+            bbox: Aabb::EMPTY,
+            first: 0,
+            count: boxes.len(),
+        });
+        bvh.subdivide(0, 1, boxes, &centroids);
+        bvh
+    }
+
+    pub fn bounding_box(&self) -> Option<Aabb> {
+        self.nodes.first().map(|n| n.bbox)
+    }
+
+    fn subdivide(
+        &mut self,
+        node_idx: usize,
+        depth: usize,
+        boxes: &[Aabb],
+        centroids: &[glam::Vec3],
+    ) {
+        let Node { first, count, .. } = self.nodes[node_idx];
+        let range = first..first + count;
+
+        let (bbox, centroid_bounds) = self.indices[range.clone()].iter().fold(
+            (Aabb::EMPTY, Aabb::EMPTY),
+            |(bbox, centroid_bounds), &i| {
+                (
+                    bbox.surrounding(&boxes[i]),
+                    centroid_bounds.grow(centroids[i].into()),
+                )
+            },
+        );
+        self.nodes[node_idx].bbox = bbox;
+
+        if count <= MAX_LEAF_SIZE || depth >= MAX_DEPTH {
+            return;
+        }
+
+        let Some((axis, split)) = Self::find_split(
+            &self.indices[range.clone()],
+            boxes,
+            centroids,
+            &bbox,
+            &centroid_bounds,
+        ) else {
+            return;
+        };
+
+        // Partition the primitives so everything left of the split comes first
+        let indices = &mut self.indices[range];
+        let mut mid = 0;
+        for i in 0..indices.len() {
+            if centroids[indices[i]][axis] < split {
+                indices.swap(i, mid);
+                mid += 1;
+            }
+        }
+        if mid == 0 || mid == count {
+            return;
+        }
+
+        let left = self.nodes.len();
+        self.nodes.push(Node {
+            bbox: Aabb::EMPTY,
+            first,
+            count: mid,
+        });
+        self.nodes.push(Node {
+            bbox: Aabb::EMPTY,
+            first: first + mid,
+            count: count - mid,
+        });
+        self.nodes[node_idx].first = left;
+        self.nodes[node_idx].count = 0;
+
+        self.subdivide(left, depth + 1, boxes, centroids);
+        self.subdivide(left + 1, depth + 1, boxes, centroids);
+    }
+
+    /// Find the axis and position of the cheapest split according to the surface area heuristic,
+    /// or `None` if keeping the node as a leaf is cheaper.
+    fn find_split(
+        indices: &[usize],
+        boxes: &[Aabb],
+        centroids: &[glam::Vec3],
+        bbox: &Aabb,
+        centroid_bounds: &Aabb,
+    ) -> Option<(usize, f32)> {
+        let mut best: Option<(usize, f32)> = None;
+        let mut best_cost = indices.len() as f32 * bbox.surface_area();
+
+        let extent = centroid_bounds.extent();
+        for axis in 0..3 {
+            if extent[axis] <= 0.0 {
+                continue;
+            }
+            let min = centroid_bounds.minimum[axis];
+            let scale = SAH_BUCKETS as f32 / extent[axis];
+
+            let mut buckets = [(0_usize, Aabb::EMPTY); SAH_BUCKETS];
+            for &i in indices {
+                let b = (((centroids[i][axis] - min) * scale) as usize).min(SAH_BUCKETS - 1);
+                buckets[b].0 += 1;
+                buckets[b].1 = buckets[b].1.surrounding(&boxes[i]);
+            }
+
+            // Sweep from the right to get the cost of everything right of each plane
+            let mut right_cost = [0.0; SAH_BUCKETS];
+            let (mut count, mut area) = (0, Aabb::EMPTY);
+            for b in (1..SAH_BUCKETS).rev() {
+                count += buckets[b].0;
+                area = area.surrounding(&buckets[b].1);
+                right_cost[b] = count as f32 * area.surface_area();
+            }
+
+            let (mut count, mut area) = (0, Aabb::EMPTY);
+            for b in 0..SAH_BUCKETS - 1 {
+                count += buckets[b].0;
+                area = area.surrounding(&buckets[b].1);
+                let cost = count as f32 * area.surface_area() + right_cost[b + 1];
+                if cost < best_cost {
+                    best_cost = cost;
+                    best = Some((axis, min + (b + 1) as f32 / scale));
+                }
+            }
+        }
+
+        best
+    }
+
+    /// Find the closest hit along `ray`, where `hit` intersects the primitive at the given index.
+    pub fn hit<'a, F>(&self, ray: Ray, t_min: f32, t_max: f32, hit: F) -> Option<HitRecord<'a>>
+    where
+        F: Fn(usize, Ray, f32, f32) -> Option<HitRecord<'a>>,
+    {
+        if self.nodes.is_empty() {
+            return None;
+        }
+
+        let mut closest_hit = None;
+        let mut closest_so_far = t_max;
+
+        let mut stack = [0; MAX_DEPTH];
+        let mut stack_len = 1;
+        while stack_len > 0 {
+            stack_len -= 1;
+            let node = &self.nodes[stack[stack_len]];
+            if !node.bbox.hit(&ray, t_min, closest_so_far) {
+                continue;
+            }
+
+            if node.count == 0 {
+                stack[stack_len] = node.first + 1;
+                stack[stack_len + 1] = node.first;
+                stack_len += 2;
+                continue;
+            }
+
+            for &i in &self.indices[node.first..node.first + node.count] {
+                if let Some(h) = hit(i, ray, t_min, closest_so_far) {
+                    closest_so_far = h.t;
+                    closest_hit = Some(h);
+                }
+            }
+        }
+        closest_hit
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{rngs::SmallRng, Rng, SeedableRng};
+
+    use super::*;
+    use crate::{
+        material::{Lambertian, Material},
+        object::{sphere, Sphere},
+        primitive::{Color, Point, Vec3},
+    };
+
+    #[test]
+    fn hit_matches_brute_force() {
+        let mut rng = SmallRng::seed_from_u64(0);
+        let mut point = |scale: f32| {
+            Point::new(
+                rng.gen_range(-scale..scale),
+                rng.gen_range(-scale..scale),
+                rng.gen_range(-scale..scale),
+            )
+        };
+
+        let spheres: Vec<_> = (0..200)
+            .map(|i| {
+                let material = Material::from(Lambertian {
+                    albedo: Color::new(i as f32, 0.0, 0.0).into(),
+                });
+                let radius = 0.1 + (i % 7) as f32 * 0.1;
+                Sphere::new(point(10.0), radius, material)
+            })
+            .collect();
+        let boxes: Vec<_> = spheres
+            .iter()
+            .map(|s| sphere::bounding_box(s).unwrap())
+            .collect();
+        let bvh = Bvh::new(&boxes);
+
+        let mut hits = 0;
+        for _ in 0..2000 {
+            let ray = Ray::new(point(15.0), Vec3::from(*point(1.0)), 0.0);
+
+            let expected = spheres
+                .iter()
+                .enumerate()
+                .filter_map(|(i, s)| Some((i, sphere::hit(s, ray, 0.001, f32::MAX)?.t)))
+                .min_by(|(_, a), (_, b)| a.total_cmp(b));
+            let actual = bvh
+                .hit(ray, 0.001, f32::MAX, |i, ray, t_min, t_max| {
+                    sphere::hit(&spheres[i], ray, t_min, t_max)
+                })
+                .map(|record| {
+                    let i = spheres
+                        .iter()
+                        .position(|s| std::ptr::eq(&s.material, record.material))
+                        .unwrap();
+                    (i, record.t)
+                });
+
+            assert_eq!(expected, actual);
+            hits += expected.is_some() as usize;
+        }
+        // Make sure the rays actually test something
+        assert!(hits > 100, "only {hits} hits");
+    }
+}
@@ -0,0 +1,120 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    material::Material,
+    object::{
+        disk,
+        frame::{self, Frame},
+        Aabb, HitRecord,
+    },
+    primitive::{Point, Ray, Vec3},
+};
+
+fn default_capped() -> bool {
+    true
+}
+
+/// A cone with its circular bottom of `radius` around `base`, narrowing to its tip at
+/// `base + axis`. With `capped = false`, the bottom is left open.
+///
+/// The surface coordinates go around the axis and up along it.
+///
+/// ```toml
+/// [[world]]
+/// Cone.base = [0, 0, 0]
+/// Cone.axis = [0, 2, 0]
+/// Cone.radius = 0.5
+/// Cone.capped = false # optional
+/// Cone.material.Lambertian.albedo = [0.5, 0.5, 0.5]
+/// ```
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct Cone {
+    pub base: Point,
+    pub axis: Vec3,
+    pub radius: f32,
+    #[serde(default = "default_capped")]
+    pub capped: bool,
+    pub material: Material,
+}
+
+impl Cone {
+    pub fn new(base: Point, axis: Vec3, radius: f32, material: Material) -> Self {
+        Self {
+            base,
+            axis,
+            radius,
+            capped: true,
+            material,
+        }
+    }
+}
+
+pub fn hit(cone: &Cone, ray: Ray, t_min: f32, mut t_max: f32) -> Option<HitRecord<'_>> {
+    let frame = Frame::new(cone.base, cone.axis);
+    let (origin, direction) = frame.to_local(ray);
+    let height = cone.axis.length();
+    let slope = cone.radius / height;
+    let k2 = slope * slope;
+
+    // The closest hit in local space, as its distance, outward normal and surface coordinates
+    let mut closest = None;
+
+    // The side, where x² + z² = (k (h - y))² with the height below the tip h - y shrinking along
+    // the ray
+    let below_tip = height - origin.y;
+    let a = direction.x * direction.x + direction.z * direction.z - k2 * direction.y * direction.y;
+    let half_b = origin.x * direction.x + origin.z * direction.z + k2 * below_tip * direction.y;
+    let c = origin.x * origin.x + origin.z * origin.z - k2 * below_tip * below_tip;
+
+    let roots = if a.abs() < 1e-8 {
+        // The ray is parallel to the side, so it only crosses it once
+        (half_b.abs() > 1e-8).then(|| [-c / (2.0 * half_b); 2])
+    } else {
+        let discriminant = half_b * half_b - a * c;
+        (discriminant >= 0.0).then(|| {
+            let sqrt_d = discriminant.sqrt();
+            let [t0, t1] = [(-half_b - sqrt_d) / a, (-half_b + sqrt_d) / a];
+            [t0.min(t1), t0.max(t1)]
+        })
+    };
+    for t in roots.into_iter().flatten() {
+        if t < t_min || t_max < t {
+            continue;
+        }
+        // The other nappe of the double cone is above the tip
+        let p = origin + direction * t;
+        if (0.0..=height).contains(&p.y) {
+            let radial = glam::Vec3::new(p.x, 0.0, p.z).normalize_or_zero();
+            let normal = (radial + glam::Vec3::Y * slope).normalize();
+            closest = Some((t, normal, [frame::azimuth(p.x, p.z), p.y / height]));
+            t_max = t;
+            break;
+        }
+    }
+
+    if cone.capped {
+        if let Some((t, uv)) = disk::hit_local(origin, direction, 0.0, cone.radius, t_min, t_max) {
+            closest = Some((t, -glam::Vec3::Y, uv));
+        }
+    }
+
+    let (t, outward_normal, uv) = closest?;
+    let (normal, front_face) = frame::face_normal(ray, frame.vector_to_world(outward_normal));
+    Some(HitRecord::new(
+        ray.at(t),
+        normal,
+        t,
+        uv,
+        front_face,
+        &cone.material,
+    ))
+}
+
+pub fn bounding_box(cone: &Cone) -> Option<Aabb> {
+    let frame = Frame::new(cone.base, cone.axis);
+    let radius = cone.radius;
+    Some(frame.bounding_box(
+        glam::Vec3::new(-radius, 0.0, -radius),
+        glam::Vec3::new(radius, cone.axis.length(), radius),
+    ))
+}
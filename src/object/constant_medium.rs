@@ -0,0 +1,64 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    material::Material,
+    object::{Aabb, HitRecord, Hittable, Object},
+    primitive::{Ray, Vec3},
+};
+
+/// A volume of smoke or fog with the same density throughout, filling a convex `boundary` object.
+/// Light scatters off it according to `material`, which is usually `Isotropic`. The material of
+/// the boundary itself is ignored.
+///
+/// Media can only be placed directly in the world, not in an instance.
+///
+/// ```toml
+/// [[world]]
+/// ConstantMedium.boundary.Sphere = { center = [0, 0, 0], radius = 1, material.Dielectric.refractive_index = 1.5 }
+/// ConstantMedium.density = 0.5
+/// ConstantMedium.material.Isotropic.albedo = [1, 1, 1]
+/// ```
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct ConstantMedium {
+    pub boundary: Box<Object>,
+    /// How likely light is to scatter per unit of distance traveled
+    pub density: f32,
+    pub material: Material,
+}
+
+impl ConstantMedium {
+    pub fn new(boundary: Object, density: f32, material: Material) -> Self {
+        Self {
+            boundary: Box::new(boundary),
+            density,
+            material,
+        }
+    }
+
+    /// The distances along `ray` where it enters and leaves the medium, within `[t_min, t_max]`
+    pub fn interval(&self, ray: Ray, t_min: f32, t_max: f32) -> Option<(f32, f32)> {
+        // The ray may start inside the boundary, so look behind it as well
+        let enter = self.boundary.hit(ray, f32::NEG_INFINITY, f32::INFINITY)?.t;
+        let exit = self.boundary.hit(ray, enter + 0.0001, f32::INFINITY)?.t;
+
+        let (enter, exit) = (enter.max(t_min), exit.min(t_max));
+        (enter < exit).then_some((enter, exit))
+    }
+
+    /// The scattering event at distance `t` along `ray` inside the medium
+    pub fn scatter_record(&self, ray: Ray, t: f32) -> HitRecord<'_> {
+        // Media have no surface, so the normal and surface coordinates are arbitrary
+        HitRecord::new(
+            ray.at(t),
+            Vec3::new(1.0, 0.0, 0.0),
+            t,
+            [0.0, 0.0],
+            true,
+            &self.material,
+        )
+    }
+}
+
+pub fn bounding_box(medium: &ConstantMedium) -> Option<Aabb> {
+    medium.boundary.bounding_box()
+}
@@ -0,0 +1,64 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    material::Material,
+    object::{quad, Aabb, HitRecord},
+    primitive::{Point, Ray, Vec3},
+};
+
+/// An axis aligned box between two opposite corners, made of six quads facing outwards.
+///
+/// ```toml
+/// [[world]]
+/// Box.minimum = [0, 0, 0]
+/// Box.maximum = [1, 2, 1]
+/// Box.material.Lambertian.albedo = [0.5, 0.5, 0.5]
+/// ```
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct Cuboid {
+    pub minimum: Point,
+    pub maximum: Point,
+    pub material: Material,
+}
+
+impl Cuboid {
+    pub fn new(a: Point, b: Point, material: Material) -> Self {
+        Self {
+            minimum: a.min(*b).into(),
+            maximum: a.max(*b).into(),
+            material,
+        }
+    }
+
+    /// The corner and edges of each side, with `u × v` pointing outwards
+    fn sides(&self) -> [(Point, Vec3, Vec3); 6] {
+        let (min, max) = (self.minimum, self.maximum);
+        let dx = Vec3::new(max.x - min.x, 0.0, 0.0);
+        let dy = Vec3::new(0.0, max.y - min.y, 0.0);
+        let dz = Vec3::new(0.0, 0.0, max.z - min.z);
+
+        [
+            (Point::new(min.x, min.y, max.z), dx, dy),  // Front
+            (Point::new(max.x, min.y, max.z), -dz, dy), // Right
+            (Point::new(max.x, min.y, min.z), -dx, dy), // Back
+            (Point::new(min.x, min.y, min.z), dz, dy),  // Left
+            (Point::new(min.x, max.y, max.z), dx, -dz), // Top
+            (Point::new(min.x, min.y, min.z), dx, dz),  // Bottom
+        ]
+    }
+}
+
+pub fn hit(cuboid: &Cuboid, ray: Ray, t_min: f32, mut t_max: f32) -> Option<HitRecord<'_>> {
+    let mut closest_hit = None;
+    for (origin, u, v) in cuboid.sides() {
+        if let Some(h) = quad::hit_at(origin, u, v, &cuboid.material, ray, t_min, t_max) {
+            t_max = h.t;
+            closest_hit = Some(h);
+        }
+    }
+    closest_hit
+}
+
+pub fn bounding_box(cuboid: &Cuboid) -> Option<Aabb> {
+    Some(Aabb::new(cuboid.minimum, cuboid.maximum).pad(1e-4))
+}
@@ -0,0 +1,110 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    material::Material,
+    object::{
+        disk,
+        frame::{self, Frame},
+        Aabb, HitRecord,
+    },
+    primitive::{Point, Ray, Vec3},
+};
+
+fn default_capped() -> bool {
+    true
+}
+
+/// A cylinder from the center of its bottom at `base` to the center of its top at `base + axis`.
+/// With `capped = false`, the ends are left open.
+///
+/// The surface coordinates go around the axis and up along it.
+///
+/// ```toml
+/// [[world]]
+/// Cylinder.base = [0, 0, 0]
+/// Cylinder.axis = [0, 2, 0]
+/// Cylinder.radius = 0.5
+/// Cylinder.capped = false # optional
+/// Cylinder.material.Lambertian.albedo = [0.5, 0.5, 0.5]
+/// ```
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct Cylinder {
+    pub base: Point,
+    pub axis: Vec3,
+    pub radius: f32,
+    #[serde(default = "default_capped")]
+    pub capped: bool,
+    pub material: Material,
+}
+
+impl Cylinder {
+    pub fn new(base: Point, axis: Vec3, radius: f32, material: Material) -> Self {
+        Self {
+            base,
+            axis,
+            radius,
+            capped: true,
+            material,
+        }
+    }
+}
+
+pub fn hit(cylinder: &Cylinder, ray: Ray, t_min: f32, mut t_max: f32) -> Option<HitRecord<'_>> {
+    let frame = Frame::new(cylinder.base, cylinder.axis);
+    let (origin, direction) = frame.to_local(ray);
+    let height = cylinder.axis.length();
+    let radius = cylinder.radius;
+
+    // The closest hit in local space, as its distance, outward normal and surface coordinates
+    let mut closest = None;
+
+    // The side, where x² + z² = r²
+    let a = direction.x * direction.x + direction.z * direction.z;
+    let half_b = origin.x * direction.x + origin.z * direction.z;
+    let c = origin.x * origin.x + origin.z * origin.z - radius * radius;
+    let discriminant = half_b * half_b - a * c;
+    if a > 1e-8 && discriminant >= 0.0 {
+        let sqrt_d = discriminant.sqrt();
+        for t in [(-half_b - sqrt_d) / a, (-half_b + sqrt_d) / a] {
+            if t < t_min || t_max < t {
+                continue;
+            }
+            let p = origin + direction * t;
+            if (0.0..=height).contains(&p.y) {
+                let normal = glam::Vec3::new(p.x, 0.0, p.z) / radius;
+                closest = Some((t, normal, [frame::azimuth(p.x, p.z), p.y / height]));
+                t_max = t;
+                break;
+            }
+        }
+    }
+
+    if cylinder.capped {
+        for (y, normal) in [(0.0, -glam::Vec3::Y), (height, glam::Vec3::Y)] {
+            if let Some((t, uv)) = disk::hit_local(origin, direction, y, radius, t_min, t_max) {
+                closest = Some((t, normal, uv));
+                t_max = t;
+            }
+        }
+    }
+
+    let (t, outward_normal, uv) = closest?;
+    let (normal, front_face) = frame::face_normal(ray, frame.vector_to_world(outward_normal));
+    Some(HitRecord::new(
+        ray.at(t),
+        normal,
+        t,
+        uv,
+        front_face,
+        &cylinder.material,
+    ))
+}
+
+pub fn bounding_box(cylinder: &Cylinder) -> Option<Aabb> {
+    let frame = Frame::new(cylinder.base, cylinder.axis);
+    let radius = cylinder.radius;
+    Some(frame.bounding_box(
+        glam::Vec3::new(-radius, 0.0, -radius),
+        glam::Vec3::new(radius, cylinder.axis.length(), radius),
+    ))
+}
@@ -0,0 +1,104 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    material::Material,
+    object::{
+        frame::{self, Frame},
+        Aabb, HitRecord,
+    },
+    primitive::{self, Point, Ray, Vec3},
+    sampler::Sampler,
+};
+
+/// A flat disk around `center`, with its front face towards `normal`. The surface coordinates go
+/// around the center and outwards.
+///
+/// ```toml
+/// [[world]]
+/// Disk.center = [0, 0, 0]
+/// Disk.normal = [0, 1, 0]
+/// Disk.radius = 1
+/// Disk.material.Lambertian.albedo = [0.5, 0.5, 0.5]
+/// ```
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct Disk {
+    pub center: Point,
+    pub normal: Vec3,
+    pub radius: f32,
+    pub material: Material,
+}
+
+impl Disk {
+    pub fn new(center: Point, normal: Vec3, radius: f32, material: Material) -> Self {
+        Self {
+            center,
+            normal,
+            radius,
+            material,
+        }
+    }
+}
+
+pub fn hit(disk: &Disk, ray: Ray, t_min: f32, t_max: f32) -> Option<HitRecord<'_>> {
+    let frame = Frame::new(disk.center, disk.normal);
+    let (origin, direction) = frame.to_local(ray);
+    let (t, uv) = hit_local(origin, direction, 0.0, disk.radius, t_min, t_max)?;
+
+    let (normal, front_face) = frame::face_normal(ray, frame.vector_to_world(glam::Vec3::Y));
+    Some(HitRecord::new(
+        ray.at(t),
+        normal,
+        t,
+        uv,
+        front_face,
+        &disk.material,
+    ))
+}
+
+/// Intersect a local ray with the disk of `radius` at height `y` facing up, also used for the caps
+/// of cylinders and cones. Returns the distance and surface coordinates.
+pub(super) fn hit_local(
+    origin: glam::Vec3,
+    direction: glam::Vec3,
+    y: f32,
+    radius: f32,
+    t_min: f32,
+    t_max: f32,
+) -> Option<(f32, [f32; 2])> {
+    // The ray is parallel to the disk
+    if direction.y.abs() < 1e-8 {
+        return None;
+    }
+    let t = (y - origin.y) / direction.y;
+    if t < t_min || t_max < t {
+        return None;
+    }
+
+    let p = origin + direction * t;
+    let distance = (p.x * p.x + p.z * p.z).sqrt();
+    if distance > radius {
+        return None;
+    }
+    Some((t, [frame::azimuth(p.x, p.z), distance / radius]))
+}
+
+pub fn bounding_box(disk: &Disk) -> Option<Aabb> {
+    let frame = Frame::new(disk.center, disk.normal);
+    let radius = glam::Vec3::new(disk.radius, 0.0, disk.radius);
+    Some(frame.bounding_box(-radius, radius).pad(1e-4))
+}
+
+/// A uniformly distributed point on the surface and its normal, for sampling lights
+pub fn sample(disk: &Disk, sampler: &mut dyn Sampler) -> (Point, Vec3) {
+    let frame = Frame::new(disk.center, disk.normal);
+    let point = primitive::sample_concentric_disk(sampler.get_2d()) * disk.radius;
+    let local = glam::Vec3::new(point.x, 0.0, point.y);
+    (
+        disk.center + frame.vector_to_world(local),
+        frame.vector_to_world(glam::Vec3::Y),
+    )
+}
+
+pub fn area(disk: &Disk) -> f32 {
+    std::f32::consts::PI * disk.radius * disk.radius
+}
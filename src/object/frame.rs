@@ -0,0 +1,70 @@
+use crate::{
+    object::Aabb,
+    primitive::{Point, Ray, Vec3},
+};
+
+/// Local coordinates with the y axis along the axis of a shape, so it can be intersected in its
+/// canonical position
+pub(super) struct Frame {
+    origin: Point,
+    x: glam::Vec3,
+    y: glam::Vec3,
+    z: glam::Vec3,
+}
+
+impl Frame {
+    pub fn new(origin: Point, axis: Vec3) -> Self {
+        let y = axis.normalize();
+        let (z, x) = y.any_orthonormal_pair();
+        Self { origin, x, y, z }
+    }
+
+    /// The origin and direction of a ray in local coordinates. The frame is orthonormal, so
+    /// distances along the ray stay the same.
+    pub fn to_local(&self, ray: Ray) -> (glam::Vec3, glam::Vec3) {
+        let origin = *(ray.origin - self.origin);
+        let direction = *ray.direction;
+        (
+            self.vector_to_local(origin),
+            self.vector_to_local(direction),
+        )
+    }
+
+    fn vector_to_local(&self, v: glam::Vec3) -> glam::Vec3 {
+        glam::Vec3::new(v.dot(self.x), v.dot(self.y), v.dot(self.z))
+    }
+
+    pub fn vector_to_world(&self, v: glam::Vec3) -> Vec3 {
+        (self.x * v.x + self.y * v.y + self.z * v.z).into()
+    }
+
+    /// A box around the local box between `minimum` and `maximum`
+    pub fn bounding_box(&self, minimum: glam::Vec3, maximum: glam::Vec3) -> Aabb {
+        (0..8)
+            .map(|i| {
+                glam::Vec3::new(
+                    if i & 1 == 0 { minimum.x } else { maximum.x },
+                    if i & 2 == 0 { minimum.y } else { maximum.y },
+                    if i & 4 == 0 { minimum.z } else { maximum.z },
+                )
+            })
+            .fold(Aabb::EMPTY, |aabb, corner| {
+                aabb.grow(self.origin + self.vector_to_world(corner))
+            })
+    }
+}
+
+/// Orient an outward normal against the ray, returning it along with whether the ray hit the front
+pub(super) fn face_normal(ray: Ray, outward_normal: Vec3) -> (Vec3, bool) {
+    let front_face = ray.direction.dot(*outward_normal) < 0.0;
+    if front_face {
+        (outward_normal, true)
+    } else {
+        (-outward_normal, false)
+    }
+}
+
+/// The angle of `(x, z)` around the y axis, scaled to `[0, 1]`
+pub(super) fn azimuth(x: f32, z: f32) -> f32 {
+    (z.atan2(x) + std::f32::consts::PI) / (2.0 * std::f32::consts::PI)
+}
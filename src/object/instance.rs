@@ -0,0 +1,334 @@
+use std::sync::Arc;
+
+use glam::{Affine3A, EulerRot, Mat3A, Quat};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::{
+    object::{Aabb, HitRecord, Hittable, Object},
+    primitive::{Point, Ray, Vec3},
+};
+
+#[derive(Error, Debug)]
+pub enum InstanceError {
+    #[error("an instance needs either an `object` or a `prototype`, but not both")]
+    Object,
+    #[error("the transform of an instance must be invertible, is it scaled by 0?")]
+    Singular,
+}
+
+/// A scale factor, either the same along every axis or per axis
+#[derive(Deserialize, Serialize, Debug, Clone, Copy)]
+#[serde(untagged)]
+pub(super) enum Scale {
+    Uniform(f32),
+    PerAxis(Vec3),
+}
+
+impl From<Scale> for glam::Vec3 {
+    fn from(scale: Scale) -> Self {
+        match scale {
+            Scale::Uniform(s) => glam::Vec3::splat(s),
+            Scale::PerAxis(s) => *s,
+        }
+    }
+}
+
+/// A rotation of `angle` degrees around `axis`
+#[derive(Deserialize, Serialize, Debug, Clone, Copy)]
+struct AxisAngle {
+    axis: Vec3,
+    angle: f32,
+}
+
+/// The rotation by degrees around the x, y and z axes, applied in that order
+pub(super) fn euler_rotation(degrees: Vec3) -> Quat {
+    Quat::from_euler(
+        EulerRot::ZYX,
+        degrees.z.to_radians(),
+        degrees.y.to_radians(),
+        degrees.x.to_radians(),
+    )
+}
+
+/// The serialized form of an instance, which is turned into an [`Instance`] on load
+#[derive(Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+struct InstanceData {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    object: Option<Arc<Object>>,
+    /// The name of a prototype in the config
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    prototype: Option<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    scale: Option<Scale>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    rotate_euler: Option<Vec3>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    rotate_axis_angle: Option<AxisAngle>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    translate: Option<Vec3>,
+}
+
+impl TryFrom<InstanceData> for Instance {
+    type Error = InstanceError;
+
+    fn try_from(data: InstanceData) -> Result<Self, Self::Error> {
+        if data.object.is_some() == data.prototype.is_some() {
+            return Err(InstanceError::Object);
+        }
+
+        let scale = data.scale.map_or(glam::Vec3::ONE, glam::Vec3::from);
+        let euler = data.rotate_euler.map_or(Quat::IDENTITY, euler_rotation);
+        let axis_angle = data.rotate_axis_angle.map_or(Quat::IDENTITY, |r| {
+            Quat::from_axis_angle(r.axis.normalize(), r.angle.to_radians())
+        });
+        let translation = data.translate.map_or(glam::Vec3::ZERO, |t| *t);
+
+        let transform =
+            Affine3A::from_scale_rotation_translation(scale, axis_angle * euler, translation);
+        let mut instance = Instance::with_transform(data.object, transform)?;
+        instance.prototype = data.prototype;
+        Ok(instance)
+    }
+}
+
+impl From<Instance> for InstanceData {
+    fn from(instance: Instance) -> Self {
+        let (scale, rotation, translation) = instance.transform.to_scale_rotation_translation();
+        let (axis, angle) = rotation.to_axis_angle();
+        Self {
+            // Instances of a prototype are written out by name again
+            object: instance.object.filter(|_| instance.prototype.is_none()),
+            prototype: instance.prototype,
+            scale: Some(Scale::PerAxis(scale.into())),
+            rotate_euler: None,
+            rotate_axis_angle: Some(AxisAngle {
+                axis: axis.into(),
+                angle: angle.to_degrees(),
+            }),
+            translate: Some(translation.into()),
+        }
+    }
+}
+
+/// An object placed with an affine transform, so the same geometry can be used several times
+/// without copying it.
+///
+/// The object is scaled, then rotated and then translated. It is either given inline, or is one of
+/// the `prototypes` of the config, which are loaded once and shared by all their instances.
+///
+/// ```toml
+/// [prototypes.bunny]
+/// Mesh.path = "bunny.obj"
+///
+/// [[world]]
+/// Instance.prototype = "bunny"
+/// Instance.scale = 10 # or [x, y, z]
+/// Instance.rotate_euler = [0, 90, 0] # degrees around x, y and z
+/// Instance.rotate_axis_angle = { axis = [1, 1, 0], angle = 45 } # after `rotate_euler`
+/// Instance.translate = [0, -1, 0]
+///
+/// [[world]]
+/// Instance.object.Sphere = { center = [0, 0, 0], radius = 1, material.Lambertian.albedo = [0.5, 0.5, 0.5] }
+/// Instance.scale = [2, 1, 1]
+/// ```
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(try_from = "InstanceData", into = "InstanceData")]
+pub struct Instance {
+    /// The name of the prototype in the config this is an instance of
+    pub prototype: Option<String>,
+    /// The object in its own space, only `None` until the prototype is linked
+    object: Option<Arc<Object>>,
+    /// From object to world space
+    transform: Affine3A,
+    /// From world to object space
+    inverse: Affine3A,
+    /// Transforms normals from object to world space
+    normal_matrix: Mat3A,
+}
+
+impl Instance {
+    pub fn new(object: Arc<Object>, transform: Affine3A) -> Result<Self, InstanceError> {
+        Self::with_transform(Some(object), transform)
+    }
+
+    fn with_transform(
+        object: Option<Arc<Object>>,
+        transform: Affine3A,
+    ) -> Result<Self, InstanceError> {
+        let determinant = transform.matrix3.determinant();
+        if determinant == 0.0 || !determinant.is_finite() {
+            return Err(InstanceError::Singular);
+        }
+
+        Ok(Self {
+            prototype: None,
+            object,
+            transform,
+            inverse: transform.inverse(),
+            normal_matrix: transform.matrix3.inverse().transpose(),
+        })
+    }
+
+    pub fn object(&self) -> Option<&Arc<Object>> {
+        self.object.as_ref()
+    }
+
+    /// Use a loaded prototype as the object
+    pub(crate) fn link(&mut self, object: Arc<Object>) {
+        self.object = Some(object);
+    }
+
+    /// The object, if it isn't shared with any other instance
+    pub(crate) fn object_mut(&mut self) -> Option<&mut Object> {
+        self.object.as_mut().and_then(Arc::get_mut)
+    }
+
+    pub fn transform(&self) -> Affine3A {
+        self.transform
+    }
+
+    pub fn translation(&self) -> Vec3 {
+        glam::Vec3::from(self.transform.translation).into()
+    }
+
+    pub fn set_translation(&mut self, translation: Vec3) {
+        self.transform.translation = (*translation).into();
+        self.inverse = self.transform.inverse();
+    }
+}
+
+pub fn hit(instance: &Instance, ray: Ray, t_min: f32, t_max: f32) -> Option<HitRecord<'_>> {
+    let object = instance.object.as_deref()?;
+
+    // The direction isn't normalized, so distances along the ray are the same in both spaces
+    let local = Ray::new(
+        instance.inverse.transform_point3(*ray.origin).into(),
+        instance.inverse.transform_vector3(*ray.direction).into(),
+        ray.time,
+    );
+    let mut record = object.hit(local, t_min, t_max)?;
+
+    record.point = ray.at(record.t);
+    record.normal = instance
+        .normal_matrix
+        .mul_vec3(*record.normal)
+        .normalize()
+        .into();
+    Some(record)
+}
+
+pub fn bounding_box(instance: &Instance) -> Option<Aabb> {
+    let Aabb { minimum, maximum } = instance.object.as_deref()?.bounding_box()?;
+
+    let corners = (0..8).map(|i| {
+        Point::new(
+            if i & 1 == 0 { minimum.x } else { maximum.x },
+            if i & 2 == 0 { minimum.y } else { maximum.y },
+            if i & 4 == 0 { minimum.z } else { maximum.z },
+        )
+    });
+    Some(corners.fold(Aabb::EMPTY, |aabb, corner| {
+        aabb.grow(instance.transform.transform_point3(*corner).into())
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        material::{Lambertian, Material},
+        object::Sphere,
+        primitive::Color,
+    };
+
+    fn sphere() -> Arc<Object> {
+        let material = Material::from(Lambertian {
+            albedo: Color::new(0.5, 0.5, 0.5).into(),
+        });
+        Arc::new(Object::Sphere(Sphere::new(
+            Point::new(0.0, 0.0, 0.0),
+            1.0,
+            material,
+        )))
+    }
+
+    fn transform() -> Affine3A {
+        Affine3A::from_scale_rotation_translation(
+            glam::Vec3::new(2.0, 1.0, 0.5),
+            Quat::from_axis_angle(glam::Vec3::new(1.0, 1.0, 0.0).normalize(), 0.7),
+            glam::Vec3::new(1.0, -2.0, 3.0),
+        )
+    }
+
+    #[test]
+    fn hits_transformed_sphere() {
+        let instance = Instance::new(sphere(), transform()).unwrap();
+        let bounds = bounding_box(&instance).unwrap();
+
+        // The sphere becomes the ellipsoid |M (p - c)| = 1 with M the inverse of the linear part
+        let center = transform().translation;
+        let m = transform().matrix3.inverse();
+
+        // Aim at points inside the sphere before it was transformed, so that every ray hits
+        for (origin, target) in [
+            ([6.0, -2.0, 3.0], [0.0, 0.0, 0.0]),
+            ([1.0, 5.0, 3.0], [0.3, 0.2, -0.1]),
+            ([-3.0, 1.0, -1.0], [-0.5, 0.0, 0.6]),
+            ([1.2, -2.1, 10.0], [0.0, -0.8, 0.0]),
+        ] {
+            let origin = glam::Vec3::from(origin);
+            let target = transform().transform_point3(target.into());
+            let ray = Ray::new(origin.into(), (target - origin).into(), 0.0);
+
+            let record =
+                hit(&instance, ray, 0.001, f32::MAX).unwrap_or_else(|| panic!("{origin:?} missed"));
+
+            let o = m * (glam::Vec3A::from(*ray.origin) - center);
+            let d = m * glam::Vec3A::from(*ray.direction);
+            let (a, h, c) = (d.dot(d), o.dot(d), o.dot(o) - 1.0);
+            let t = (-h - (h * h - a * c).sqrt()) / a;
+            let point = ray.at(t);
+            // The gradient of |M (p - c)|²
+            let normal = (m.transpose() * m * (glam::Vec3A::from(*point) - center)).normalize();
+
+            assert!((record.t - t).abs() < 1e-4, "{} != {t}", record.t);
+            assert!(record.point.distance(*point) < 1e-4);
+            assert!(glam::Vec3A::from(*record.normal).distance(normal) < 1e-4);
+            assert!(record.is_front_face);
+
+            let inside = |x: f32, min: f32, max: f32| (min - 1e-4..=max + 1e-4).contains(&x);
+            assert!(inside(point.x, bounds.minimum.x, bounds.maximum.x));
+            assert!(inside(point.y, bounds.minimum.y, bounds.maximum.y));
+            assert!(inside(point.z, bounds.minimum.z, bounds.maximum.z));
+        }
+
+        let ray = Ray::new(Point::new(6.0, 5.0, 3.0), Vec3::new(-1.0, 0.0, 0.0), 0.0);
+        assert!(hit(&instance, ray, 0.001, f32::MAX).is_none());
+    }
+
+    #[test]
+    fn serialization_round_trip() {
+        let instance = Instance::new(sphere(), transform()).unwrap();
+        let config = toml::to_string(&Object::Instance(instance.clone())).unwrap();
+        let Object::Instance(parsed) = toml::from_str(&config).unwrap() else {
+            panic!("not an instance: {config}");
+        };
+        assert!(parsed.transform().abs_diff_eq(instance.transform(), 1e-5));
+        assert_eq!(parsed.object(), instance.object());
+
+        // Instances of a prototype only refer to it by name
+        let mut instance = instance;
+        instance.prototype = Some("ball".to_owned());
+        let config = toml::to_string(&Object::Instance(instance.clone())).unwrap();
+        assert!(!config.contains("Sphere"), "{config}");
+        let Object::Instance(parsed) = toml::from_str(&config).unwrap() else {
+            panic!("not an instance: {config}");
+        };
+        assert_eq!(parsed.prototype.as_deref(), Some("ball"));
+        assert!(parsed.object().is_none());
+        assert!(parsed.transform().abs_diff_eq(instance.transform(), 1e-5));
+    }
+}
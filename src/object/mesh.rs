@@ -0,0 +1,307 @@
+use std::path::PathBuf;
+
+use glam::{Affine3A, Quat};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::{
+    material::{Lambertian, Material},
+    object::{
+        instance::{self, Scale},
+        obj, triangle, Aabb, Bvh, HitRecord,
+    },
+    primitive::{Color, Point, Ray, Vec3},
+};
+
+#[derive(Error, Debug)]
+pub enum MeshError {
+    #[error("face {face} references vertex {index}, but the mesh only has {count} vertices")]
+    IndexOutOfBounds {
+        face: usize,
+        index: u32,
+        count: usize,
+    },
+    #[error("face {face} references material {index}, but the mesh only has {count} materials")]
+    MaterialOutOfBounds {
+        face: usize,
+        index: u32,
+        count: usize,
+    },
+    #[error("the mesh has {expected} {parent} but {count} {attribute}")]
+    AttributeCount {
+        attribute: &'static str,
+        parent: &'static str,
+        expected: usize,
+        count: usize,
+    },
+    #[error("a mesh needs either a `path` or inline `vertices` and `indices`")]
+    MissingGeometry,
+    #[error("a mesh with inline geometry needs a `material` or `materials`")]
+    MissingMaterial,
+    #[error("could not read {path}: {source}")]
+    Io {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    #[error("{path}:{line}: {message}")]
+    Parse {
+        path: PathBuf,
+        line: usize,
+        message: String,
+    },
+}
+
+/// The serialized form of a mesh, which is validated and turned into a [`Mesh`] on load
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct MeshData {
+    /// An OBJ file to load the geometry (and materials) from
+    path: Option<PathBuf>,
+
+    #[serde(default)]
+    vertices: Vec<Point>,
+    #[serde(default)]
+    normals: Vec<Vec3>,
+    #[serde(default)]
+    uvs: Vec<[f32; 2]>,
+    #[serde(default)]
+    indices: Vec<[u32; 3]>,
+
+    /// A single material used for the whole mesh, overriding any materials from the OBJ file
+    material: Option<Material>,
+    #[serde(default)]
+    materials: Vec<Material>,
+    #[serde(default)]
+    face_materials: Vec<u32>,
+
+    scale: Option<Scale>,
+    /// Rotation in degrees around the x, y and z axes, applied in that order
+    rotate: Option<Vec3>,
+    translate: Option<Vec3>,
+}
+
+impl TryFrom<MeshData> for Mesh {
+    type Error = MeshError;
+
+    fn try_from(data: MeshData) -> Result<Self, Self::Error> {
+        let (mut vertices, mut normals, uvs, indices, mut materials, mut face_materials) =
+            if let Some(path) = &data.path {
+                let obj = obj::load(path)?;
+
+                // Faces without a material from the OBJ file get a plain grey one
+                let fallback = obj.materials.len() as u32;
+                let mut materials = obj.materials;
+                let face_materials: Vec<_> = obj
+                    .face_materials
+                    .iter()
+                    .map(|m| m.unwrap_or(fallback))
+                    .collect();
+                if face_materials.contains(&fallback) {
+                    materials.push(Material::from(Lambertian {
+                        albedo: Color::new(0.5, 0.5, 0.5).into(),
+                    }));
+                }
+
+                (
+                    obj.vertices,
+                    obj.normals,
+                    obj.uvs,
+                    obj.indices,
+                    materials,
+                    face_materials,
+                )
+            } else if !data.vertices.is_empty() && !data.indices.is_empty() {
+                (
+                    data.vertices,
+                    data.normals,
+                    data.uvs,
+                    data.indices,
+                    data.materials,
+                    data.face_materials,
+                )
+            } else {
+                return Err(MeshError::MissingGeometry);
+            };
+
+        if let Some(material) = data.material {
+            materials = vec![material];
+            face_materials.clear();
+        }
+        if materials.is_empty() {
+            return Err(MeshError::MissingMaterial);
+        }
+
+        let scale = data.scale.map_or(glam::Vec3::ONE, glam::Vec3::from);
+        let rotation = data.rotate.map_or(Quat::IDENTITY, instance::euler_rotation);
+        let translation = data.translate.map_or(glam::Vec3::ZERO, |t| *t);
+
+        let transform = Affine3A::from_scale_rotation_translation(scale, rotation, translation);
+        if transform != Affine3A::IDENTITY {
+            let normal_matrix = transform.matrix3.inverse().transpose();
+            for vertex in vertices.iter_mut() {
+                *vertex = transform.transform_point3(**vertex).into();
+            }
+            for normal in normals.iter_mut() {
+                *normal = (normal_matrix * **normal).normalize().into();
+            }
+        }
+
+        Mesh::new(vertices, normals, uvs, indices, materials, face_materials)
+    }
+}
+
+/// An indexed triangle mesh with its own BVH over its triangles.
+///
+/// In a scene config, the vertex attributes and indices are either given inline:
+///
+/// ```toml
+/// [[world]]
+/// Mesh.vertices = [[0, 0, 0], [1, 0, 0], [0, 1, 0]]
+/// Mesh.normals = [[0, 0, 1], [0, 0, 1], [0, 0, 1]] # optional
+/// Mesh.uvs = [[0, 0], [1, 0], [0, 1]] # optional
+/// Mesh.indices = [[0, 1, 2]]
+/// Mesh.material.Lambertian.albedo = [0.5, 0.5, 0.5]
+/// ```
+///
+/// or loaded from a Wavefront OBJ file, relative to the config file. Materials from the MTL files
+/// it references are used unless a `material` is given.
+///
+/// ```toml
+/// [[world]]
+/// Mesh.path = "bunny.obj"
+/// Mesh.scale = 10 # or [x, y, z]
+/// Mesh.rotate = [0, 90, 0] # degrees around x, y and z
+/// Mesh.translate = [0, -1, 0]
+/// ```
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(try_from = "MeshData")]
+pub struct Mesh {
+    vertices: Vec<Point>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    normals: Vec<Vec3>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    uvs: Vec<[f32; 2]>,
+    indices: Vec<[u32; 3]>,
+    pub materials: Vec<Material>,
+    /// Index into `materials` for each face. If empty, every face uses the first material.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    face_materials: Vec<u32>,
+
+    #[serde(skip)]
+    bvh: Bvh,
+}
+
+impl Mesh {
+    /// Create a mesh from a list of vertices and the vertex indices of each triangle.
+    ///
+    /// `normals` and `uvs` are per-vertex attributes, and may be left empty. `face_materials` is
+    /// the index into `materials` for each triangle, and may be left empty to use the first
+    /// material for the whole mesh.
+    pub fn new(
+        vertices: Vec<Point>,
+        normals: Vec<Vec3>,
+        uvs: Vec<[f32; 2]>,
+        indices: Vec<[u32; 3]>,
+        materials: Vec<Material>,
+        face_materials: Vec<u32>,
+    ) -> Result<Self, MeshError> {
+        for (attribute, parent, expected, count) in [
+            ("normals", "vertices", vertices.len(), normals.len()),
+            ("uvs", "vertices", vertices.len(), uvs.len()),
+            (
+                "face materials",
+                "faces",
+                indices.len(),
+                face_materials.len(),
+            ),
+        ] {
+            if count != 0 && count != expected {
+                return Err(MeshError::AttributeCount {
+                    attribute,
+                    parent,
+                    expected,
+                    count,
+                });
+            }
+        }
+        if materials.is_empty() {
+            return Err(MeshError::MissingMaterial);
+        }
+        for (face, triangle) in indices.iter().enumerate() {
+            if let Some(&index) = triangle.iter().find(|&&i| i as usize >= vertices.len()) {
+                return Err(MeshError::IndexOutOfBounds {
+                    face,
+                    index,
+                    count: vertices.len(),
+                });
+            }
+        }
+        for (face, &index) in face_materials.iter().enumerate() {
+            if index as usize >= materials.len() {
+                return Err(MeshError::MaterialOutOfBounds {
+                    face,
+                    index,
+                    count: materials.len(),
+                });
+            }
+        }
+
+        let mut mesh = Self {
+            vertices,
+            normals,
+            uvs,
+            indices,
+            materials,
+            face_materials,
+            bvh: Bvh::default(),
+        };
+        let boxes: Vec<_> = (0..mesh.indices.len())
+            .map(|face| triangle::vertices_bounding_box(&mesh.face_vertices(face)))
+            .collect();
+        mesh.bvh = Bvh::new(&boxes);
+        tracing::debug!(triangles = mesh.indices.len(), "Built mesh BVH");
+
+        Ok(mesh)
+    }
+
+    pub fn triangle_count(&self) -> usize {
+        self.indices.len()
+    }
+
+    #[inline]
+    fn face_vertices(&self, face: usize) -> [Point; 3] {
+        self.indices[face].map(|i| self.vertices[i as usize])
+    }
+
+    fn hit_face(&self, face: usize, ray: Ray, t_min: f32, t_max: f32) -> Option<HitRecord<'_>> {
+        let vertices = self.face_vertices(face);
+        let (t, u, v) = triangle::intersect(&vertices, ray, t_min, t_max)?;
+
+        let [a, b, c] = self.indices[face].map(|i| i as usize);
+        let normals =
+            (!self.normals.is_empty()).then(|| [self.normals[a], self.normals[b], self.normals[c]]);
+        let uvs = (!self.uvs.is_empty()).then(|| [self.uvs[a], self.uvs[b], self.uvs[c]]);
+        let material = self.face_materials.get(face).map_or(0, |&m| m as usize);
+
+        Some(triangle::record(
+            &vertices,
+            normals,
+            uvs,
+            &self.materials[material],
+            ray,
+            t,
+            u,
+            v,
+        ))
+    }
+}
+
+pub fn hit(mesh: &Mesh, ray: Ray, t_min: f32, t_max: f32) -> Option<HitRecord<'_>> {
+    mesh.bvh.hit(ray, t_min, t_max, |face, ray, t_min, t_max| {
+        mesh.hit_face(face, ray, t_min, t_max)
+    })
+}
+
+pub fn bounding_box(mesh: &Mesh) -> Option<Aabb> {
+    mesh.bvh.bounding_box()
+}
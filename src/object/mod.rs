@@ -2,63 +2,332 @@ use derive_more::From;
 use serde::{Deserialize, Serialize};
 
 mod aabb;
+mod bvh;
+mod cone;
+mod constant_medium;
+mod cuboid;
+mod cylinder;
+mod disk;
+mod frame;
+mod instance;
+mod mesh;
+mod moving_sphere;
+mod obj;
+mod plane;
+mod quad;
 mod sphere;
+mod torus;
+mod triangle;
 
+pub use aabb::Aabb;
+pub use bvh::Bvh;
+pub use cone::Cone;
+pub use constant_medium::ConstantMedium;
+pub use cuboid::Cuboid;
+pub use cylinder::Cylinder;
+pub use disk::Disk;
+pub use instance::{Instance, InstanceError};
+pub use mesh::{Mesh, MeshError};
+pub use moving_sphere::MovingSphere;
+pub use plane::Plane;
+pub use quad::Quad;
 pub use sphere::Sphere;
+pub use torus::Torus;
+pub use triangle::Triangle;
 
 use crate::{
     material::Material,
     primitive::{Point, Ray, Vec3},
+    sampler::Sampler,
 };
 
+/// Where and how a ray hit an object
 #[derive(Debug, Copy, Clone, PartialEq)]
-pub struct HitRecord {
+pub struct HitRecord<'a> {
     pub point: Point,
+    /// The unit surface normal, facing against the ray
     pub normal: Vec3,
+    /// The distance along the ray, in multiples of its direction
     pub t: f32,
+    /// Surface coordinates of the hit, each in `[0, 1]`
+    pub uv: [f32; 2],
+    /// Whether the ray hit the outside of the surface
     pub is_front_face: bool,
-    pub material: Material,
+    pub material: &'a Material,
+    /// The index of the object in the lights of the world, if it is sampled as a light
+    pub light: Option<usize>,
 }
 
-impl HitRecord {
+impl<'a> HitRecord<'a> {
     pub fn new(
         point: Point,
         normal: Vec3,
         t: f32,
+        uv: [f32; 2],
         is_front_face: bool,
-        material: Material,
+        material: &'a Material,
     ) -> Self {
         Self {
             point,
             normal,
             t,
+            uv,
             is_front_face,
             material,
+            light: None,
         }
     }
 }
 
+/// A point sampled on one of the lights in a world, as seen from another point
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct LightSample<'a> {
+    pub point: Point,
+    /// The unit surface normal of the light, on either side
+    pub normal: Vec3,
+    /// The probability density of sampling the direction towards the point, per unit solid angle
+    pub pdf: f32,
+    pub material: &'a Material,
+}
+
+/// Anything a ray can hit
 pub trait Hittable {
-    fn hit(&self, ray: Ray, t_min: f32, t_max: f32) -> Option<HitRecord>;
+    /// The closest hit along `ray` with `t` in `[t_min, t_max]`, if any
+    fn hit(&self, ray: Ray, t_min: f32, t_max: f32) -> Option<HitRecord<'_>>;
 }
 
-#[derive(Debug, PartialEq, Deserialize, Serialize, From)]
+/// The objects a world can be made of
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize, From)]
 pub enum Object {
     Sphere(Sphere),
+    MovingSphere(MovingSphere),
+    Triangle(Triangle),
+    Quad(Quad),
+    Plane(Plane),
+    Box(Cuboid),
+    Disk(Disk),
+    Cylinder(Cylinder),
+    Cone(Cone),
+    Torus(Torus),
+    Mesh(Mesh),
+    Instance(Instance),
+    ConstantMedium(ConstantMedium),
+}
+
+/// The probability density per unit solid angle of sampling `point` with `normal` from `origin`,
+/// when points are distributed uniformly over a surface with the given area
+fn area_pdf(area: f32, origin: Point, point: Point, normal: Vec3) -> f32 {
+    let to_light = point - origin;
+    let cosine = normal.dot(to_light.normalize()).abs();
+    to_light.length_squared() / (cosine * area)
+}
+
+impl Object {
+    /// The emissive material of the object, if its surface can be sampled as a light
+    fn light_material(&self) -> Option<&Material> {
+        let material = match self {
+            Object::Sphere(s) => &s.material,
+            Object::Triangle(t) => &t.material,
+            Object::Quad(q) => &q.material,
+            Object::Disk(d) => &d.material,
+            _ => return None,
+        };
+        material.is_emissive().then_some(material)
+    }
+
+    /// A point on the surface to illuminate `origin` with and its normal, if the object supports it
+    fn sample_surface(&self, sampler: &mut dyn Sampler, origin: Point) -> Option<(Point, Vec3)> {
+        match self {
+            Object::Sphere(s) => Some(sphere::sample(s, sampler, origin)),
+            Object::Triangle(t) => Some(triangle::sample(t, sampler)),
+            Object::Quad(q) => Some(quad::sample(q, sampler)),
+            Object::Disk(d) => Some(disk::sample(d, sampler)),
+            _ => None,
+        }
+    }
+
+    /// The probability density per unit solid angle of [`Object::sample_surface`] sampling
+    /// `point` with `normal` from `origin`
+    fn surface_pdf(&self, origin: Point, point: Point, normal: Vec3) -> f32 {
+        match self {
+            Object::Sphere(s) => sphere::pdf(s, origin, point, normal),
+            Object::Triangle(t) => area_pdf(triangle::area(t), origin, point, normal),
+            Object::Quad(q) => area_pdf(quad::area(q), origin, point, normal),
+            Object::Disk(d) => area_pdf(disk::area(d), origin, point, normal),
+            _ => 0.0,
+        }
+    }
+
+    /// The bounding box of the object, or `None` if the object is unbounded
+    pub fn bounding_box(&self) -> Option<Aabb> {
+        match self {
+            Object::Sphere(s) => sphere::bounding_box(s),
+            Object::MovingSphere(s) => moving_sphere::bounding_box(s),
+            Object::Triangle(t) => triangle::bounding_box(t),
+            Object::Quad(q) => quad::bounding_box(q),
+            Object::Plane(_) => None,
+            Object::Box(b) => cuboid::bounding_box(b),
+            Object::Disk(d) => disk::bounding_box(d),
+            Object::Cylinder(c) => cylinder::bounding_box(c),
+            Object::Cone(c) => cone::bounding_box(c),
+            Object::Torus(t) => torus::bounding_box(t),
+            Object::Mesh(m) => mesh::bounding_box(m),
+            Object::Instance(i) => instance::bounding_box(i),
+            Object::ConstantMedium(m) => constant_medium::bounding_box(m),
+        }
+    }
+}
+
+impl Hittable for Object {
+    fn hit(&self, ray: Ray, t_min: f32, t_max: f32) -> Option<HitRecord<'_>> {
+        match self {
+            Object::Sphere(s) => sphere::hit(s, ray, t_min, t_max),
+            Object::MovingSphere(s) => moving_sphere::hit(s, ray, t_min, t_max),
+            Object::Triangle(t) => triangle::hit(t, ray, t_min, t_max),
+            Object::Quad(q) => quad::hit(q, ray, t_min, t_max),
+            Object::Plane(p) => plane::hit(p, ray, t_min, t_max),
+            Object::Box(b) => cuboid::hit(b, ray, t_min, t_max),
+            Object::Disk(d) => disk::hit(d, ray, t_min, t_max),
+            Object::Cylinder(c) => cylinder::hit(c, ray, t_min, t_max),
+            Object::Cone(c) => cone::hit(c, ray, t_min, t_max),
+            Object::Torus(t) => torus::hit(t, ray, t_min, t_max),
+            Object::Mesh(m) => mesh::hit(m, ray, t_min, t_max),
+            Object::Instance(i) => instance::hit(i, ray, t_min, t_max),
+            // Where a ray scatters inside a medium is random, so the tracer samples it separately
+            Object::ConstantMedium(_) => None,
+        }
+    }
 }
 
-impl Hittable for &[Object] {
-    fn hit(&self, ray: Ray, t_min: f32, t_max: f32) -> Option<HitRecord> {
-        let mut closest_hit = None;
-        let mut closest_so_far = t_max;
+/// The objects in a scene, along with an acceleration structure over them.
+///
+/// The acceleration structure must be rebuilt with [`World::rebuild`] whenever an object moves or
+/// changes size.
+#[derive(Debug, Clone, Default)]
+pub struct World {
+    objects: Vec<Object>,
+    bvh: Bvh,
+    /// Indices of the objects in the BVH, in the order it was built with
+    bounded: Vec<usize>,
+    /// Indices of objects without a bounding box, which are tested separately
+    unbounded: Vec<usize>,
+    /// Indices of the participating media, which rays aren't intersected with
+    media: Vec<usize>,
+    /// Indices of the emissive objects that are sampled directly
+    lights: Vec<usize>,
+}
+
+impl World {
+    pub fn new(objects: Vec<Object>) -> Self {
+        let mut world = Self {
+            objects,
+            ..Default::default()
+        };
+        world.rebuild();
+        world
+    }
+
+    /// Mutable access to the objects. Call [`World::rebuild`] after changing their bounds.
+    pub fn objects_mut(&mut self) -> &mut [Object] {
+        &mut self.objects
+    }
+
+    /// The participating media in the world
+    pub fn media(&self) -> impl Iterator<Item = &ConstantMedium> {
+        self.media.iter().map(|&idx| match &self.objects[idx] {
+            Object::ConstantMedium(medium) => medium,
+            _ => unreachable!("not a medium"),
+        })
+    }
+
+    /// Pick one of the lights at random and sample a point on it to illuminate `origin` with
+    pub fn sample_light(
+        &self,
+        sampler: &mut dyn Sampler,
+        origin: Point,
+    ) -> Option<LightSample<'_>> {
+        if self.lights.is_empty() {
+            return None;
+        }
+        let count = self.lights.len();
+        let light = ((sampler.get_1d() * count as f32) as usize).min(count - 1);
+        let object = &self.objects[self.lights[light]];
+
+        let (point, normal) = object.sample_surface(sampler, origin)?;
+        Some(LightSample {
+            point,
+            normal,
+            pdf: self.light_pdf(light, origin, point, normal),
+            material: object.light_material()?,
+        })
+    }
+
+    /// The probability density per unit solid angle of [`World::sample_light`] sampling `point`
+    /// with `normal` on the light with index `light` from `origin`
+    pub fn light_pdf(&self, light: usize, origin: Point, point: Point, normal: Vec3) -> f32 {
+        self.objects[self.lights[light]].surface_pdf(origin, point, normal)
+            / self.lights.len() as f32
+    }
+
+    /// The fraction of light that gets through the media along `ray` between `t_min` and `t_max`
+    pub fn transmittance(&self, ray: Ray, t_min: f32, t_max: f32) -> f32 {
+        let length = ray.direction.length();
+        self.media()
+            .filter_map(|medium| {
+                let (enter, exit) = medium.interval(ray, t_min, t_max)?;
+                Some((-medium.density * (exit - enter) * length).exp())
+            })
+            .product()
+    }
+
+    pub fn rebuild(&mut self) {
+        self.lights = (0..self.objects.len())
+            .filter(|&idx| self.objects[idx].light_material().is_some())
+            .collect();
+        self.media = (0..self.objects.len())
+            .filter(|&idx| matches!(self.objects[idx], Object::ConstantMedium(_)))
+            .collect();
+
+        let (bounded, boxes): (Vec<_>, Vec<_>) = self
+            .objects
+            .iter()
+            .enumerate()
+            .filter(|(idx, _)| self.media.binary_search(idx).is_err())
+            .filter_map(|(idx, obj)| obj.bounding_box().map(|b| (idx, b)))
+            .unzip();
+
+        self.unbounded = (0..self.objects.len())
+            .filter(|idx| bounded.binary_search(idx).is_err())
+            .filter(|idx| self.media.binary_search(idx).is_err())
+            .collect();
+        self.bvh = Bvh::new(&boxes);
+
+        tracing::debug!(
+            objects = self.objects.len(),
+            unbounded = self.unbounded.len(),
+            media = self.media.len(),
+            lights = self.lights.len(),
+            "Rebuilt BVH"
+        );
+        self.bounded = bounded;
+    }
+}
 
-        for hittable in self.iter() {
-            let hit = match hittable {
-                Object::Sphere(s) => sphere::hit(s, ray, t_min, closest_so_far),
-            };
+impl Hittable for World {
+    fn hit(&self, ray: Ray, t_min: f32, t_max: f32) -> Option<HitRecord<'_>> {
+        let mut closest_hit = self.bvh.hit(ray, t_min, t_max, |idx, ray, t_min, t_max| {
+            let idx = self.bounded[idx];
+            let record = self.objects[idx].hit(ray, t_min, t_max)?;
+            Some(HitRecord {
+                light: self.lights.binary_search(&idx).ok(),
+                ..record
+            })
+        });
+        let mut closest_so_far = closest_hit.map_or(t_max, |h| h.t);
 
+        for &idx in &self.unbounded {
             // If we hit something
-            if let Some(h) = hit {
+            if let Some(h) = self.objects[idx].hit(ray, t_min, closest_so_far) {
                 closest_hit = Some(h);
                 closest_so_far = h.t;
             }
@@ -0,0 +1,49 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    material::Material,
+    object::{sphere, Aabb, HitRecord},
+    primitive::{Point, Ray},
+};
+
+/// A sphere moving in a straight line from `center0` at time 0 to `center1` at time 1
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct MovingSphere {
+    pub center0: Point,
+    pub center1: Point,
+    pub radius: f32,
+    pub material: Material,
+}
+
+impl MovingSphere {
+    pub fn new(center0: Point, center1: Point, radius: f32, material: Material) -> Self {
+        Self {
+            center0,
+            center1,
+            radius,
+            material,
+        }
+    }
+
+    /// Where the center is at `time`
+    pub fn center(&self, time: f32) -> Point {
+        self.center0 + (self.center1 - self.center0) * time
+    }
+}
+
+pub fn hit(sphere: &MovingSphere, ray: Ray, t_min: f32, t_max: f32) -> Option<HitRecord<'_>> {
+    sphere::hit_at(
+        sphere.center(ray.time),
+        sphere.radius,
+        &sphere.material,
+        ray,
+        t_min,
+        t_max,
+    )
+}
+
+pub fn bounding_box(sphere: &MovingSphere) -> Option<Aabb> {
+    let start = sphere::box_at(sphere.center0, sphere.radius);
+    let end = sphere::box_at(sphere.center1, sphere.radius);
+    Some(start.surrounding(&end))
+}
@@ -0,0 +1,427 @@
+//! A loader for Wavefront OBJ meshes and their MTL material libraries.
+//!
+//! Only the geometry and material statements needed for rendering are supported, i.e. `v`, `vt`,
+//! `vn`, `f`, `mtllib` and `usemtl` in OBJ files, and `newmtl`, `Kd`, `Ks`, `Ns`, `Ni`, `d`, `Tr`
+//! and `Ke` in MTL files. Everything else is ignored.
+
+use std::{collections::HashMap, path::Path};
+
+use crate::{
+    material::{Dielectric, DiffuseLight, Lambertian, Material, Metal},
+    object::MeshError,
+    primitive::{Color, Point, Vec3},
+};
+
+/// The contents of an OBJ file, flattened so every vertex has a single index
+#[derive(Debug, Default)]
+pub struct ObjData {
+    pub vertices: Vec<Point>,
+    pub normals: Vec<Vec3>,
+    pub uvs: Vec<[f32; 2]>,
+    pub indices: Vec<[u32; 3]>,
+    /// Materials from the MTL libraries referenced by the file
+    pub materials: Vec<Material>,
+    /// Index into `materials` for each face, or `None` if the face has no material
+    pub face_materials: Vec<Option<u32>>,
+}
+
+/// A material as described in an MTL file
+#[derive(Debug)]
+struct MtlMaterial {
+    name: String,
+    diffuse: Color,
+    specular: Color,
+    emission: Color,
+    shininess: f32,
+    refractive_index: Option<f32>,
+    dissolve: f32,
+}
+
+impl MtlMaterial {
+    fn new(name: String) -> Self {
+        Self {
+            name,
+            diffuse: Color::new(0.8, 0.8, 0.8),
+            specular: Color::ZERO,
+            emission: Color::ZERO,
+            shininess: 0.0,
+            refractive_index: None,
+            dissolve: 1.0,
+        }
+    }
+
+    /// Map the material onto the closest material the tracer supports
+    fn to_material(&self) -> Material {
+        if !self.emission.is_near_zero() {
+            let intensity = self.emission.max_element();
+            Material::from(DiffuseLight {
+                color: self.emission / intensity,
+                intensity,
+            })
+        } else if self.dissolve < 1.0 {
+            Material::from(Dielectric {
+                refractive_index: self.refractive_index.unwrap_or(1.5),
+            })
+        } else if self.specular.max_element() > self.diffuse.max_element() {
+            // Blender exports roughness as Ns = 1000 * (1 - roughness)^2
+            let roughness = 1.0 - (self.shininess / 1000.0).clamp(0.0, 1.0).sqrt();
+            Material::from(Metal {
+                albedo: self.specular.into(),
+                fuzz: roughness,
+            })
+        } else {
+            Material::from(Lambertian {
+                albedo: self.diffuse.into(),
+            })
+        }
+    }
+}
+
+fn parse_error(path: &Path, line: usize, message: impl Into<String>) -> MeshError {
+    MeshError::Parse {
+        path: path.to_owned(),
+        line: line + 1,
+        message: message.into(),
+    }
+}
+
+fn read(path: &Path) -> Result<String, MeshError> {
+    std::fs::read_to_string(path).map_err(|source| MeshError::Io {
+        path: path.to_owned(),
+        source,
+    })
+}
+
+fn parse_floats<'a, const N: usize>(
+    mut args: impl Iterator<Item = &'a str>,
+    path: &Path,
+    line: usize,
+) -> Result<[f32; N], MeshError> {
+    let mut values = [0.0; N];
+    for value in values.iter_mut() {
+        let arg = args
+            .next()
+            .ok_or_else(|| parse_error(path, line, format!("expected {N} numbers")))?;
+        *value = arg
+            .parse()
+            .map_err(|_| parse_error(path, line, format!("invalid number `{arg}`")))?;
+    }
+    Ok(values)
+}
+
+fn parse_vec3<'a>(
+    args: impl Iterator<Item = &'a str>,
+    path: &Path,
+    line: usize,
+) -> Result<Vec3, MeshError> {
+    let [x, y, z] = parse_floats(args, path, line)?;
+    Ok(Vec3::new(x, y, z))
+}
+
+/// Resolve a one-based, possibly negative (relative to the end) OBJ index
+fn resolve_index(index: &str, len: usize, path: &Path, line: usize) -> Result<usize, MeshError> {
+    let parsed: i64 = index
+        .parse()
+        .map_err(|_| parse_error(path, line, format!("invalid index `{index}`")))?;
+    let resolved = match parsed {
+        i if i > 0 => i - 1,
+        i if i < 0 => len as i64 + i,
+        _ => -1,
+    };
+    if resolved < 0 || resolved >= len as i64 {
+        return Err(parse_error(
+            path,
+            line,
+            format!("index {parsed} is out of bounds"),
+        ));
+    }
+    Ok(resolved as usize)
+}
+
+fn load_mtl(path: &Path) -> Result<Vec<MtlMaterial>, MeshError> {
+    let mut materials: Vec<MtlMaterial> = Vec::new();
+
+    for (line_idx, line) in read(path)?.lines().enumerate() {
+        let mut args = line.split_whitespace();
+        let Some(keyword) = args.next() else {
+            continue;
+        };
+        if keyword == "newmtl" {
+            let name = args.collect::<Vec<_>>().join(" ");
+            materials.push(MtlMaterial::new(name));
+            continue;
+        }
+        if keyword.starts_with('#') {
+            continue;
+        }
+
+        let Some(material) = materials.last_mut() else {
+            return Err(parse_error(
+                path,
+                line_idx,
+                format!("`{keyword}` before any `newmtl`"),
+            ));
+        };
+        match keyword {
+            "Kd" => material.diffuse = parse_vec3(args, path, line_idx)?,
+            "Ks" => material.specular = parse_vec3(args, path, line_idx)?,
+            "Ke" => material.emission = parse_vec3(args, path, line_idx)?,
+            "Ns" => [material.shininess] = parse_floats(args, path, line_idx)?,
+            "Ni" => material.refractive_index = Some(parse_floats::<1>(args, path, line_idx)?[0]),
+            "d" => [material.dissolve] = parse_floats(args, path, line_idx)?,
+            "Tr" => material.dissolve = 1.0 - parse_floats::<1>(args, path, line_idx)?[0],
+            _ => {}
+        }
+    }
+
+    Ok(materials)
+}
+
+pub fn load(path: &Path) -> Result<ObjData, MeshError> {
+    let directory = path.parent().map(Path::to_owned).unwrap_or_default();
+
+    let mut positions: Vec<Point> = Vec::new();
+    let mut normals: Vec<Vec3> = Vec::new();
+    let mut uvs: Vec<[f32; 2]> = Vec::new();
+
+    let mut data = ObjData::default();
+    let mut material_names: HashMap<String, u32> = HashMap::new();
+    let mut current_material = None;
+
+    // Every distinct (position, uv, normal) triplet becomes its own vertex
+    let mut vertex_map: HashMap<(usize, Option<usize>, Option<usize>), u32> = HashMap::new();
+    let mut corners: Vec<(usize, Option<usize>, Option<usize>)> = Vec::new();
+
+    for (line_idx, line) in read(path)?.lines().enumerate() {
+        let mut args = line.split_whitespace();
+        match args.next() {
+            Some("v") => positions.push(parse_vec3(args, path, line_idx)?),
+            Some("vn") => normals.push(parse_vec3(args, path, line_idx)?),
+            Some("vt") => {
+                let mut uv = [0.0; 2];
+                for (value, arg) in uv.iter_mut().zip(args) {
+                    *value = arg.parse().map_err(|_| {
+                        parse_error(path, line_idx, format!("invalid number `{arg}`"))
+                    })?;
+                }
+                uvs.push(uv);
+            }
+            Some("f") => {
+                corners.clear();
+                for corner in args {
+                    let mut parts = corner.split('/');
+                    let position = resolve_index(
+                        parts.next().unwrap_or_default(),
+                        positions.len(),
+                        path,
+                        line_idx,
+                    )?;
+                    let uv = match parts.next() {
+                        Some(i) if !i.is_empty() => {
+                            Some(resolve_index(i, uvs.len(), path, line_idx)?)
+                        }
+                        _ => None,
+                    };
+                    let normal = match parts.next() {
+                        Some(i) if !i.is_empty() => {
+                            Some(resolve_index(i, normals.len(), path, line_idx)?)
+                        }
+                        _ => None,
+                    };
+                    corners.push((position, uv, normal));
+                }
+                if corners.len() < 3 {
+                    return Err(parse_error(
+                        path,
+                        line_idx,
+                        "face has fewer than 3 vertices",
+                    ));
+                }
+
+                let mut vertex_index = |key| {
+                    *vertex_map.entry(key).or_insert_with(|| {
+                        data.vertices.push(positions[key.0]);
+                        data.uvs.push(key.1.map_or([0.0, 0.0], |i| uvs[i]));
+                        data.normals.push(key.2.map_or(Vec3::ZERO, |i| normals[i]));
+                        (data.vertices.len() - 1) as u32
+                    })
+                };
+                let face: Vec<_> = corners.iter().map(|&c| vertex_index(c)).collect();
+
+                // Triangulate polygons as a fan around the first vertex
+                for pair in face[1..].windows(2) {
+                    data.indices.push([face[0], pair[0], pair[1]]);
+                    data.face_materials.push(current_material);
+                }
+            }
+            Some("mtllib") => {
+                let name = args.collect::<Vec<_>>().join(" ");
+                for material in load_mtl(&directory.join(name))? {
+                    material_names.insert(material.name.clone(), data.materials.len() as u32);
+                    data.materials.push(material.to_material());
+                }
+            }
+            Some("usemtl") => {
+                let name = args.collect::<Vec<_>>().join(" ");
+                current_material = material_names.get(&name).copied();
+                if current_material.is_none() {
+                    tracing::warn!(material = name, "Unknown material in {}", path.display());
+                }
+            }
+            _ => {}
+        }
+    }
+
+    // Attributes that only some vertices have can't be interpolated, so drop them
+    if vertex_map.keys().any(|(_, uv, _)| uv.is_none()) {
+        data.uvs.clear();
+    }
+    if vertex_map.keys().any(|(_, _, normal)| normal.is_none()) {
+        data.normals.clear();
+    }
+
+    tracing::info!(
+        path = %path.display(),
+        vertices = data.vertices.len(),
+        triangles = data.indices.len(),
+        materials = data.materials.len(),
+        "Loaded OBJ"
+    );
+    Ok(data)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::*;
+    use crate::texture::Texture;
+
+    /// Write `files` into a fresh directory named after the test and return the path of the first
+    fn write_files(test: &str, files: &[(&str, &str)]) -> PathBuf {
+        let directory = std::env::temp_dir().join(format!("raytracing-obj-{test}"));
+        let _ = std::fs::remove_dir_all(&directory);
+        std::fs::create_dir_all(&directory).unwrap();
+        for (name, contents) in files {
+            std::fs::write(directory.join(name), contents).unwrap();
+        }
+        directory.join(files[0].0)
+    }
+
+    fn load_str(test: &str, obj: &str) -> ObjData {
+        load(&write_files(test, &[("mesh.obj", obj)])).unwrap()
+    }
+
+    const SQUARE: &str = "v 0 0 0\nv 1 0 0\nv 1 1 0\nv 0 1 0\n";
+
+    #[test]
+    fn negative_indices() {
+        let data = load_str(
+            "negative",
+            &format!("{SQUARE}f -4 -3 -2\nv 5 5 5\nf -1 -2 -3\n"),
+        );
+        assert_eq!(data.indices, [[0, 1, 2], [3, 4, 2]]);
+        assert_eq!(data.vertices[3], Point::new(5.0, 5.0, 5.0));
+        assert_eq!(data.vertices[4], Point::new(0.0, 1.0, 0.0));
+
+        let path = write_files(
+            "out_of_bounds",
+            &[("mesh.obj", &format!("{SQUARE}f -5 1 2\n"))],
+        );
+        assert!(matches!(load(&path), Err(MeshError::Parse { line: 5, .. })));
+    }
+
+    #[test]
+    fn polygons_are_fans() {
+        let data = load_str("fan", &format!("{SQUARE}v 0.5 2 0\nf 1 2 3 5 4\n"));
+        assert_eq!(data.indices, [[0, 1, 2], [0, 2, 3], [0, 3, 4]]);
+        assert_eq!(data.vertices.len(), 5);
+    }
+
+    #[test]
+    fn corner_attributes() {
+        let obj = "vt 0 0\nvt 1 0\nvt 0 1\nvn 0 0 1\nvn 0 0 -1\n";
+
+        // A position used with two different normals becomes two vertices
+        let data = load_str(
+            "full_corners",
+            &format!("{SQUARE}{obj}f 1/1/1 2/2/1 4/3/1\nf 1/1/2 4/3/2 3/2/2\n"),
+        );
+        assert_eq!(data.indices, [[0, 1, 2], [3, 4, 5]]);
+        assert_eq!(
+            data.uvs,
+            [
+                [0.0, 0.0],
+                [1.0, 0.0],
+                [0.0, 1.0],
+                [0.0, 0.0],
+                [0.0, 1.0],
+                [1.0, 0.0]
+            ]
+        );
+        assert_eq!(data.normals[0], Vec3::new(0.0, 0.0, 1.0));
+        assert_eq!(data.normals[3], Vec3::new(0.0, 0.0, -1.0));
+        assert_eq!(data.vertices[0], data.vertices[3]);
+
+        // Normals without uvs, and attributes only some corners have are dropped
+        let data = load_str(
+            "normal_corners",
+            &format!("{SQUARE}{obj}f 1//1 2//1 3//1\n"),
+        );
+        assert!(data.uvs.is_empty());
+        assert_eq!(data.normals.len(), 3);
+        let data = load_str("mixed_corners", &format!("{SQUARE}{obj}f 1/1/1 2//1 3/2\n"));
+        assert!(data.uvs.is_empty());
+        assert!(data.normals.is_empty());
+        assert_eq!(data.indices, [[0, 1, 2]]);
+    }
+
+    #[test]
+    fn mtl_materials() {
+        let mtl = "\
+newmtl diffuse
+Kd 0.5 0.25 0.125
+newmtl metal
+Kd 0.1 0.1 0.1
+Ks 0.9 0.8 0.7
+Ns 250
+newmtl glass
+d 0.5
+Ni 1.3
+newmtl light
+Kd 1 1 1
+Ke 4 2 0
+";
+        let obj = format!(
+            "mtllib scene.mtl\n{SQUARE}f 1 2 3\nusemtl metal\nf 1 2 3\n\
+             usemtl glass\nf 1 2 3\nusemtl light\nf 1 2 3\nusemtl diffuse\nf 1 2 3\n"
+        );
+        let path = write_files("mtl", &[("mesh.obj", &obj), ("scene.mtl", mtl)]);
+        let data = load(&path).unwrap();
+
+        assert_eq!(
+            data.materials,
+            [
+                Material::from(Lambertian {
+                    albedo: Texture::Solid(Color::new(0.5, 0.25, 0.125)),
+                }),
+                // Ns = 1000 * (1 - roughness)²
+                Material::from(Metal {
+                    albedo: Texture::Solid(Color::new(0.9, 0.8, 0.7)),
+                    fuzz: 0.5,
+                }),
+                Material::from(Dielectric {
+                    refractive_index: 1.3,
+                }),
+                Material::from(DiffuseLight {
+                    color: Color::new(1.0, 0.5, 0.0),
+                    intensity: 4.0,
+                }),
+            ]
+        );
+        assert_eq!(
+            data.face_materials,
+            [None, Some(1), Some(2), Some(3), Some(0)]
+        );
+    }
+}
@@ -0,0 +1,66 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    material::Material,
+    object::HitRecord,
+    primitive::{Point, Ray, Vec3},
+};
+
+/// An infinite plane through `point`, with its front face towards `normal`. Its surface
+/// coordinates repeat every unit along the plane.
+///
+/// ```toml
+/// [[world]]
+/// Plane.point = [0, -0.5, 0]
+/// Plane.normal = [0, 1, 0]
+/// Plane.material.Lambertian.albedo = [0.5, 0.5, 0.5]
+/// ```
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct Plane {
+    pub point: Point,
+    pub normal: Vec3,
+    pub material: Material,
+}
+
+impl Plane {
+    pub fn new(point: Point, normal: Vec3, material: Material) -> Self {
+        Self {
+            point,
+            normal,
+            material,
+        }
+    }
+}
+
+pub fn hit(plane: &Plane, ray: Ray, t_min: f32, t_max: f32) -> Option<HitRecord<'_>> {
+    let normal = plane.normal.normalize();
+
+    // The ray is parallel to the plane
+    let denominator = normal.dot(*ray.direction);
+    if denominator.abs() < 1e-8 {
+        return None;
+    }
+    let t = normal.dot(*(plane.point - ray.origin)) / denominator;
+    if t < t_min || t_max < t {
+        return None;
+    }
+
+    let point = ray.at(t);
+    let (tangent, bitangent) = normal.any_orthonormal_pair();
+    let p = *(point - plane.point);
+    let uv = [
+        p.dot(tangent).rem_euclid(1.0),
+        p.dot(bitangent).rem_euclid(1.0),
+    ];
+
+    let front_face = denominator < 0.0;
+    let normal = if front_face { normal } else { -normal };
+    Some(HitRecord::new(
+        point,
+        normal.into(),
+        t,
+        uv,
+        front_face,
+        &plane.material,
+    ))
+}
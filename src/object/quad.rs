@@ -0,0 +1,119 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    material::Material,
+    object::{Aabb, HitRecord},
+    primitive::{Point, Ray, Vec3},
+    sampler::Sampler,
+};
+
+/// A parallelogram spanned by the edges `u` and `v` from the corner at `origin`. The front face is
+/// the one `u × v` points out of.
+///
+/// ```toml
+/// [[world]]
+/// Quad.origin = [-1, 0, -1]
+/// Quad.u = [2, 0, 0]
+/// Quad.v = [0, 0, 2]
+/// Quad.material.Lambertian.albedo = [0.5, 0.5, 0.5]
+/// ```
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct Quad {
+    pub origin: Point,
+    pub u: Vec3,
+    pub v: Vec3,
+    pub material: Material,
+}
+
+impl Quad {
+    pub fn new(origin: Point, u: Vec3, v: Vec3, material: Material) -> Self {
+        Self {
+            origin,
+            u,
+            v,
+            material,
+        }
+    }
+}
+
+pub fn hit(quad: &Quad, ray: Ray, t_min: f32, t_max: f32) -> Option<HitRecord<'_>> {
+    hit_at(
+        quad.origin,
+        quad.u,
+        quad.v,
+        &quad.material,
+        ray,
+        t_min,
+        t_max,
+    )
+}
+
+/// Intersect the quad spanned by `u` and `v` from `origin`, shared with boxes
+pub(super) fn hit_at(
+    origin: Point,
+    u: Vec3,
+    v: Vec3,
+    material: &Material,
+    ray: Ray,
+    t_min: f32,
+    t_max: f32,
+) -> Option<HitRecord<'_>> {
+    let n = u.cross(*v);
+    let normal = n.normalize();
+
+    // The ray is parallel to the quad
+    let denominator = normal.dot(*ray.direction);
+    if denominator.abs() < 1e-8 {
+        return None;
+    }
+    let t = normal.dot(*(origin - ray.origin)) / denominator;
+    if t < t_min || t_max < t {
+        return None;
+    }
+
+    // Express the hit point in terms of the edges, which must both be in [0, 1] inside the quad
+    let point = ray.at(t);
+    let w = n / n.length_squared();
+    let p = *(point - origin);
+    let alpha = w.dot(p.cross(*v));
+    let beta = w.dot(u.cross(p));
+    if !(0.0..=1.0).contains(&alpha) || !(0.0..=1.0).contains(&beta) {
+        return None;
+    }
+
+    let front_face = denominator < 0.0;
+    let normal = if front_face { normal } else { -normal };
+    Some(HitRecord::new(
+        point,
+        normal.into(),
+        t,
+        [alpha, beta],
+        front_face,
+        material,
+    ))
+}
+
+pub fn bounding_box(quad: &Quad) -> Option<Aabb> {
+    Some(box_at(quad.origin, quad.u, quad.v))
+}
+
+pub(super) fn box_at(origin: Point, u: Vec3, v: Vec3) -> Aabb {
+    // Quads lying in an axis aligned plane would get a flat box, which rays always miss
+    Aabb::EMPTY
+        .grow(origin)
+        .grow(origin + u)
+        .grow(origin + v)
+        .grow(origin + u + v)
+        .pad(1e-4)
+}
+
+/// A uniformly distributed point on the surface and its normal, for sampling lights
+pub fn sample(quad: &Quad, sampler: &mut dyn Sampler) -> (Point, Vec3) {
+    let [s, t] = sampler.get_2d();
+    let point = quad.origin + quad.u * s + quad.v * t;
+    (point, quad.u.cross(*quad.v).normalize().into())
+}
+
+pub fn area(quad: &Quad) -> f32 {
+    quad.u.cross(*quad.v).length()
+}
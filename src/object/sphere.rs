@@ -1,11 +1,12 @@
 use crate::{
     material::Material,
-    object::HitRecord,
-    primitive::{Point, Ray, Vec3},
+    object::{Aabb, HitRecord},
+    primitive::{self, Onb, Point, Ray, Vec3},
+    sampler::Sampler,
 };
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, PartialEq, Deserialize, Serialize)]
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
 pub struct Sphere {
     pub center: Point,
     pub radius: f32,
@@ -22,11 +23,41 @@ impl Sphere {
     }
 }
 
-pub fn hit(sphere: &Sphere, ray: Ray, t_min: f32, t_max: f32) -> Option<HitRecord> {
-    let oc = ray.origin - sphere.center;
+/// Spherical coordinates of a point on the unit sphere, with `u` going around the y axis starting
+/// from -x and `v` going from -y to +y.
+fn uv(point: &Vec3) -> [f32; 2] {
+    let theta = (-point.y).clamp(-1.0, 1.0).acos();
+    let phi = (-point.z).atan2(point.x) + std::f32::consts::PI;
+    [
+        phi / (2.0 * std::f32::consts::PI),
+        theta / std::f32::consts::PI,
+    ]
+}
+
+pub fn hit(sphere: &Sphere, ray: Ray, t_min: f32, t_max: f32) -> Option<HitRecord<'_>> {
+    hit_at(
+        sphere.center,
+        sphere.radius,
+        &sphere.material,
+        ray,
+        t_min,
+        t_max,
+    )
+}
+
+/// Intersect a sphere at `center`, shared with spheres that move
+pub(super) fn hit_at(
+    center: Point,
+    radius: f32,
+    material: &Material,
+    ray: Ray,
+    t_min: f32,
+    t_max: f32,
+) -> Option<HitRecord<'_>> {
+    let oc = ray.origin - center;
     let a = ray.direction.length_squared();
     let h = oc.dot(*ray.direction);
-    let c = oc.length_squared() - sphere.radius.powi(2);
+    let c = oc.length_squared() - radius.powi(2);
 
     let discriminant = h.powi(2) - a * c;
     if discriminant < 0.0 {
@@ -43,7 +74,8 @@ pub fn hit(sphere: &Sphere, ray: Ray, t_min: f32, t_max: f32) -> Option<HitRecor
     }
 
     let point = ray.at(t);
-    let outward_normal = (point - sphere.center) / sphere.radius;
+    let outward_normal = (point - center) / radius;
+    let uv = uv(&outward_normal);
     let front_face = ray.direction.dot(*outward_normal) < 0.0;
     let outward_normal = if front_face {
         outward_normal
@@ -54,7 +86,138 @@ pub fn hit(sphere: &Sphere, ray: Ray, t_min: f32, t_max: f32) -> Option<HitRecor
         point,
         outward_normal,
         t,
+        uv,
         front_face,
-        sphere.material,
+        material,
     ))
 }
+
+pub fn bounding_box(sphere: &Sphere) -> Option<Aabb> {
+    Some(box_at(sphere.center, sphere.radius))
+}
+
+pub(super) fn box_at(center: Point, radius: f32) -> Aabb {
+    // Negative radii are used for hollow spheres
+    let radius = radius.abs();
+    let radius = Vec3::new(radius, radius, radius);
+    Aabb::new(center - radius, center + radius)
+}
+
+/// The cosine of the half-angle of the cone of directions from `origin` towards the sphere, or
+/// `None` if `origin` is inside it
+fn cos_theta_max(sphere: &Sphere, origin: Point) -> Option<f32> {
+    let sin2_theta_max = sphere.radius * sphere.radius / (sphere.center - origin).length_squared();
+    (sin2_theta_max < 1.0).then(|| (1.0 - sin2_theta_max).sqrt())
+}
+
+/// A point on the surface and its normal, for sampling lights. From outside the sphere, only the
+/// side facing `origin` is sampled, uniformly by solid angle.
+pub fn sample(sphere: &Sphere, sampler: &mut dyn Sampler, origin: Point) -> (Point, Vec3) {
+    let radius = sphere.radius.abs();
+    let Some(cos_theta_max) = cos_theta_max(sphere, origin) else {
+        let normal = primitive::sample_uniform_sphere(sampler.get_2d());
+        return (sphere.center + normal * radius, normal);
+    };
+
+    let to_center = sphere.center - origin;
+    let direction =
+        Onb::new(to_center).to_world(primitive::sample_cone(sampler.get_2d(), cos_theta_max));
+    // The nearest intersection along the direction, the distance from the center to the direction
+    // is found with a projection rather than from the ray-sphere discriminant to keep precision for
+    // small, distant spheres
+    let along = direction.dot(*to_center);
+    let offset = (to_center - direction * along).length_squared();
+    let t = along - (radius * radius - offset).max(0.0).sqrt();
+    let point = origin + direction * t;
+    (point, (point - sphere.center).normalize().into())
+}
+
+/// The probability density per unit solid angle of [`sample`] sampling `point` with `normal`
+/// from `origin`
+pub fn pdf(sphere: &Sphere, origin: Point, point: Point, normal: Vec3) -> f32 {
+    match cos_theta_max(sphere, origin) {
+        Some(cos_theta_max) => 1.0 / (2.0 * std::f32::consts::PI * (1.0 - cos_theta_max)),
+        None => super::area_pdf(area(sphere), origin, point, normal),
+    }
+}
+
+pub fn area(sphere: &Sphere) -> f32 {
+    4.0 * std::f32::consts::PI * sphere.radius * sphere.radius
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        material::Lambertian,
+        primitive::Color,
+        sampler::{Sampler, SamplerType},
+    };
+
+    fn sphere(center: Point, radius: f32) -> Sphere {
+        let material = Material::from(Lambertian {
+            albedo: Color::new(0.5, 0.5, 0.5).into(),
+        });
+        Sphere::new(center, radius, material)
+    }
+
+    fn sampler() -> Box<dyn Sampler> {
+        let mut sampler = SamplerType::Independent.build(0, 0, 1);
+        sampler.start_pixel_sample([0, 0], 0);
+        sampler
+    }
+
+    #[test]
+    fn samples_are_visible_from_outside() {
+        let sphere = sphere(Point::new(1.0, 2.0, 3.0), 0.5);
+        let origin = Point::new(0.0, 0.0, 0.0);
+        let mut sampler = sampler();
+
+        // ∫ cos θ dω over a cone of half-angle θmax around the axis is π sin² θmax
+        let axis = sphere.center.normalize();
+        let expected = std::f32::consts::PI * 0.25 / sphere.center.length_squared();
+
+        let count = 20000;
+        let mut estimate = 0.0;
+        for _ in 0..count {
+            let (point, normal) = sample(&sphere, &mut *sampler, origin);
+            assert!(((point - sphere.center).length() - 0.5).abs() < 1e-4);
+            assert!(normal.dot(*(origin - point)) >= 0.0, "{point:?} faces away");
+
+            // The first hit towards the sample is the sample itself, which has the same pdf
+            let record = hit(&sphere, Ray::new(origin, point - origin, 0.0), 0.001, 2.0).unwrap();
+            assert!((record.t - 1.0).abs() < 1e-3, "t = {}", record.t);
+            let density = pdf(&sphere, origin, point, normal);
+            assert_eq!(density, pdf(&sphere, origin, record.point, record.normal));
+
+            estimate += axis.dot((point - origin).normalize()) / density;
+        }
+        estimate /= count as f32;
+        assert!(
+            (estimate / expected - 1.0).abs() < 0.01,
+            "{estimate} != {expected}"
+        );
+    }
+
+    #[test]
+    fn samples_cover_the_sphere_from_inside() {
+        let sphere = sphere(Point::new(0.0, 0.0, 0.0), 2.0);
+        let origin = Point::new(0.5, 0.0, 0.0);
+        let mut sampler = sampler();
+
+        // The solid angle of everything around the origin
+        let count = 20000;
+        let mut estimate = 0.0;
+        for _ in 0..count {
+            let (point, normal) = sample(&sphere, &mut *sampler, origin);
+            assert!(((point - sphere.center).length() - 2.0).abs() < 1e-4);
+            estimate += 1.0 / pdf(&sphere, origin, point, normal);
+        }
+        estimate /= count as f32;
+        let expected = 4.0 * std::f32::consts::PI;
+        assert!(
+            (estimate / expected - 1.0).abs() < 0.02,
+            "{estimate} != {expected}"
+        );
+    }
+}
@@ -0,0 +1,307 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    material::Material,
+    object::{
+        frame::{self, Frame},
+        Aabb, HitRecord,
+    },
+    primitive::{Point, Ray, Vec3},
+};
+
+/// A ring around `axis` through `center`. The tube with `minor_radius` runs at `major_radius` from
+/// the center.
+///
+/// The surface coordinates go around the axis and around the tube.
+///
+/// ```toml
+/// [[world]]
+/// Torus.center = [0, 0, 0]
+/// Torus.axis = [0, 1, 0]
+/// Torus.major_radius = 1
+/// Torus.minor_radius = 0.25
+/// Torus.material.Lambertian.albedo = [0.5, 0.5, 0.5]
+/// ```
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct Torus {
+    pub center: Point,
+    pub axis: Vec3,
+    pub major_radius: f32,
+    pub minor_radius: f32,
+    pub material: Material,
+}
+
+impl Torus {
+    pub fn new(
+        center: Point,
+        axis: Vec3,
+        major_radius: f32,
+        minor_radius: f32,
+        material: Material,
+    ) -> Self {
+        Self {
+            center,
+            axis,
+            major_radius,
+            minor_radius,
+            material,
+        }
+    }
+}
+
+pub fn hit(torus: &Torus, ray: Ray, t_min: f32, t_max: f32) -> Option<HitRecord<'_>> {
+    let frame = Frame::new(torus.center, torus.axis);
+    let (origin, direction) = frame.to_local(ray);
+    let (major, minor) = (torus.major_radius as f64, torus.minor_radius as f64);
+
+    // Solve in double precision with a unit direction, starting from the closest point to the
+    // center along the ray, otherwise the quartic loses too much precision far away
+    let length = direction.length() as f64;
+    let d = direction.as_dvec3() / length;
+    let start = -origin.as_dvec3().dot(d);
+    let o = origin.as_dvec3() + d * start;
+
+    // (|p|² - R² - r²)² + 4R²(y² - r²) = 0 along p = o + t d
+    let f = o.dot(d);
+    let e = o.length_squared() - major * major - minor * minor;
+    let four_r2 = 4.0 * major * major;
+    let coefficients = [
+        e * e + four_r2 * (o.y * o.y - minor * minor),
+        4.0 * f * e + 2.0 * four_r2 * o.y * d.y,
+        2.0 * e + 4.0 * f * f + four_r2 * d.y * d.y,
+        4.0 * f,
+    ];
+
+    let t = solve_quartic(coefficients)
+        .into_iter()
+        .flatten()
+        .map(|t| ((t + start) / length) as f32)
+        .filter(|t| (t_min..=t_max).contains(t))
+        .min_by(f32::total_cmp)?;
+
+    let p = origin + direction * t;
+    let ring = glam::Vec3::new(p.x, 0.0, p.z).normalize_or_zero() * torus.major_radius;
+    let outward_normal = (p - ring).normalize();
+    let tube = (p.x * p.x + p.z * p.z).sqrt() - torus.major_radius;
+    let uv = [
+        frame::azimuth(p.x, p.z),
+        (p.y.atan2(tube) + std::f32::consts::PI) / (2.0 * std::f32::consts::PI),
+    ];
+
+    let (normal, front_face) = frame::face_normal(ray, frame.vector_to_world(outward_normal));
+    Some(HitRecord::new(
+        ray.at(t),
+        normal,
+        t,
+        uv,
+        front_face,
+        &torus.material,
+    ))
+}
+
+pub fn bounding_box(torus: &Torus) -> Option<Aabb> {
+    let frame = Frame::new(torus.center, torus.axis);
+    let outer = torus.major_radius + torus.minor_radius;
+    let extent = glam::Vec3::new(outer, torus.minor_radius, outer);
+    Some(frame.bounding_box(-extent, extent))
+}
+
+/// The real roots of `x⁴ + c[3] x³ + c[2] x² + c[1] x + c[0]`, with Ferrari's method
+fn solve_quartic(c: [f64; 4]) -> [Option<f64>; 4] {
+    let [c0, c1, c2, c3] = c;
+
+    // Substitute x = y - c3 / 4 to get y⁴ + p y² + q y + r
+    let shift = c3 / 4.0;
+    let sq = shift * shift;
+    let p = c2 - 6.0 * sq;
+    let q = c1 - 2.0 * c2 * shift + 8.0 * sq * shift;
+    let r = c0 - c1 * shift + c2 * sq - 3.0 * sq * sq;
+
+    let mut roots = [None; 4];
+    if q.abs() < 1e-12 {
+        // Biquadratic, a quadratic in y²
+        for (i, z) in solve_quadratic(1.0, p, r).into_iter().flatten().enumerate() {
+            if z >= 0.0 {
+                roots[2 * i] = Some(z.sqrt() - shift);
+                roots[2 * i + 1] = Some(-z.sqrt() - shift);
+            }
+        }
+    } else {
+        // Complete the square with the largest root m of the resolvent cubic
+        // 8m³ + 8p m² + (2p² - 8r) m - q² = 0, so that y⁴ + p y² + q y + r factors into
+        // (y² + m + p/2)² - 2m (y - q / 4m)²
+        let m = solve_cubic(p, p * p / 4.0 - r, -q * q / 8.0)
+            .into_iter()
+            .flatten()
+            .fold(f64::NEG_INFINITY, f64::max);
+        if m <= 0.0 {
+            return roots;
+        }
+        let s = (2.0 * m).sqrt();
+        let t = q / (2.0 * s);
+        for (i, sign) in [1.0, -1.0].into_iter().enumerate() {
+            // y² ∓ s y + (m + p/2 ± t)
+            let quadratic = solve_quadratic(1.0, -sign * s, m + p / 2.0 + sign * t);
+            for (j, y) in quadratic.into_iter().enumerate() {
+                roots[2 * i + j] = y.map(|y| y - shift);
+            }
+        }
+    }
+
+    // Polish the roots with a Newton step each, which the precision of the cubic limits
+    roots.map(|root| {
+        root.map(|x| {
+            let value = (((x + c3) * x + c2) * x + c1) * x + c0;
+            let slope = ((4.0 * x + 3.0 * c3) * x + 2.0 * c2) * x + c1;
+            if slope.abs() > 1e-12 {
+                x - value / slope
+            } else {
+                x
+            }
+        })
+    })
+}
+
+/// The real roots of `a x² + b x + c`
+fn solve_quadratic(a: f64, b: f64, c: f64) -> [Option<f64>; 2] {
+    let discriminant = b * b - 4.0 * a * c;
+    if discriminant < 0.0 {
+        return [None, None];
+    }
+    // Avoid cancellation between b and the square root
+    let q = -0.5 * (b + b.signum() * discriminant.sqrt());
+    if q == 0.0 {
+        return [Some(0.0), Some(0.0)];
+    }
+    [Some(q / a), Some(c / q)]
+}
+
+/// The real roots of `x³ + a x² + b x + c`
+fn solve_cubic(a: f64, b: f64, c: f64) -> [Option<f64>; 3] {
+    let q = (a * a - 3.0 * b) / 9.0;
+    let r = (2.0 * a * a * a - 9.0 * a * b + 27.0 * c) / 54.0;
+    let shift = a / 3.0;
+
+    if r * r < q * q * q {
+        // Three real roots
+        let theta = (r / (q * q * q).sqrt()).clamp(-1.0, 1.0).acos();
+        let scale = -2.0 * q.sqrt();
+        let tau = 2.0 * std::f64::consts::PI;
+        [
+            Some(scale * (theta / 3.0).cos() - shift),
+            Some(scale * ((theta + tau) / 3.0).cos() - shift),
+            Some(scale * ((theta - tau) / 3.0).cos() - shift),
+        ]
+    } else {
+        let u = -r.signum() * (r.abs() + (r * r - q * q * q).sqrt()).cbrt();
+        let v = if u == 0.0 { 0.0 } else { q / u };
+        [Some(u + v - shift), None, None]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{material::Lambertian, primitive::Color};
+
+    /// The coefficients of the monic quartic with the given roots
+    fn quartic(roots: [f64; 4]) -> [f64; 4] {
+        let mut c = [1.0, 0.0, 0.0, 0.0, 0.0];
+        for root in roots {
+            for i in (1..5).rev() {
+                c[i] = c[i - 1] - root * c[i];
+            }
+            c[0] *= -root;
+        }
+        // `c[i]` is the coefficient of xⁱ, and the x⁴ one is left out
+        [c[0], c[1], c[2], c[3]]
+    }
+
+    fn assert_roots(expected: [f64; 4], tolerance: f64) {
+        let found: Vec<_> = solve_quartic(quartic(expected))
+            .into_iter()
+            .flatten()
+            .collect();
+        assert!(!found.is_empty(), "no roots for {expected:?}");
+        let close = |a: f64, b: f64| (a - b).abs() < tolerance;
+        for root in expected {
+            assert!(
+                found.iter().any(|&x| close(x, root)),
+                "missed {root} in {found:?}"
+            );
+        }
+        for x in &found {
+            assert!(
+                expected.iter().any(|&root| close(*x, root)),
+                "spurious {x} in {found:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn quartic_distinct_roots() {
+        assert_roots([1.0, 2.0, 3.0, 4.0], 1e-9);
+        assert_roots([-5.0, -0.5, 0.25, 7.0], 1e-9);
+    }
+
+    #[test]
+    fn quartic_repeated_roots() {
+        assert_roots([1.0, 1.0, 3.0, 4.0], 1e-6);
+        assert_roots([-1.0, 2.0, 2.0, 2.0], 1e-4);
+        assert_roots([1.0, 1.0, 2.0, 2.0], 1e-6);
+    }
+
+    #[test]
+    fn quartic_depressed_without_linear_term() {
+        // (x² - 1)(x² - 4) has q = 0 after the substitution
+        assert_roots([-2.0, -1.0, 1.0, 2.0], 1e-9);
+        // As does anything symmetric about its center, here x = 3
+        assert_roots([1.0, 2.5, 3.5, 5.0], 1e-9);
+    }
+
+    #[test]
+    fn quartic_without_real_roots() {
+        // (x² + 1)(x² + 2)
+        assert!(solve_quartic([2.0, 0.0, 3.0, 0.0])
+            .iter()
+            .all(Option::is_none));
+    }
+
+    fn torus() -> Torus {
+        let material = Material::from(Lambertian {
+            albedo: Color::new(0.5, 0.5, 0.5).into(),
+        });
+        Torus::new(
+            Point::new(0.0, 0.0, 0.0),
+            Vec3::new(0.0, 1.0, 0.0),
+            1.0,
+            0.25,
+            material,
+        )
+    }
+
+    #[test]
+    fn ray_along_axis_misses() {
+        let torus = torus();
+        let ray = Ray::new(Point::new(0.0, 5.0, 0.0), Vec3::new(0.0, -1.0, 0.0), 0.0);
+        assert!(hit(&torus, ray, 0.001, f32::MAX).is_none());
+    }
+
+    #[test]
+    fn ray_through_tube_hits() {
+        let torus = torus();
+        let ray = Ray::new(Point::new(-3.0, 0.0, 0.0), Vec3::new(1.0, 0.0, 0.0), 0.0);
+        let record = hit(&torus, ray, 0.001, f32::MAX).unwrap();
+        assert!((record.t - 1.75).abs() < 1e-5, "t = {}", record.t);
+        assert!(record.is_front_face);
+
+        // The nearest hit past the first one is on the inside of the tube
+        let record = hit(&torus, ray, 1.8, f32::MAX).unwrap();
+        assert!((record.t - 2.25).abs() < 1e-5, "t = {}", record.t);
+
+        // t is in units of the unnormalized direction
+        let ray = Ray::new(Point::new(-3.0, 0.0, 0.0), Vec3::new(2.0, 0.0, 0.0), 0.0);
+        let record = hit(&torus, ray, 0.001, f32::MAX).unwrap();
+        assert!((record.t - 0.875).abs() < 1e-5, "t = {}", record.t);
+    }
+}
@@ -0,0 +1,147 @@
+use crate::{
+    material::Material,
+    object::{Aabb, HitRecord},
+    primitive::{self, Point, Ray, Vec3},
+    sampler::Sampler,
+};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct Triangle {
+    pub vertices: [Point; 3],
+    /// Per-vertex normals for smooth shading, the face normal is used if absent
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub normals: Option<[Vec3; 3]>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub uvs: Option<[[f32; 2]; 3]>,
+    pub material: Material,
+}
+
+impl Triangle {
+    pub fn new(vertices: [Point; 3], material: Material) -> Self {
+        Self {
+            vertices,
+            normals: None,
+            uvs: None,
+            material,
+        }
+    }
+}
+
+/// Möller–Trumbore ray-triangle intersection.
+///
+/// Returns the distance along the ray and the barycentric coordinates of the hit relative to the
+/// second and third vertex.
+#[inline]
+pub fn intersect(
+    vertices: &[Point; 3],
+    ray: Ray,
+    t_min: f32,
+    t_max: f32,
+) -> Option<(f32, f32, f32)> {
+    let edge1 = vertices[1] - vertices[0];
+    let edge2 = vertices[2] - vertices[0];
+
+    let p = ray.direction.cross(*edge2);
+    let determinant = edge1.dot(p);
+    // The ray is parallel to the triangle
+    if determinant.abs() < f32::EPSILON {
+        return None;
+    }
+    let inv_determinant = 1.0 / determinant;
+
+    let s = ray.origin - vertices[0];
+    let u = s.dot(p) * inv_determinant;
+    if !(0.0..=1.0).contains(&u) {
+        return None;
+    }
+
+    let q = s.cross(*edge1);
+    let v = ray.direction.dot(q) * inv_determinant;
+    if v < 0.0 || u + v > 1.0 {
+        return None;
+    }
+
+    let t = edge2.dot(q) * inv_determinant;
+    if t < t_min || t_max < t {
+        return None;
+    }
+    Some((t, u, v))
+}
+
+/// Build the hit record for a triangle given the result of [`intersect`], interpolating the
+/// per-vertex attributes if present.
+#[allow(clippy::too_many_arguments)]
+pub fn record<'a>(
+    vertices: &[Point; 3],
+    normals: Option<[Vec3; 3]>,
+    uvs: Option<[[f32; 2]; 3]>,
+    material: &'a Material,
+    ray: Ray,
+    t: f32,
+    u: f32,
+    v: f32,
+) -> HitRecord<'a> {
+    let w = 1.0 - u - v;
+
+    let face_normal: Vec3 = (vertices[1] - vertices[0])
+        .cross(*(vertices[2] - vertices[0]))
+        .normalize()
+        .into();
+    let front_face = ray.direction.dot(*face_normal) < 0.0;
+
+    let normal: Vec3 = match normals {
+        Some([n0, n1, n2]) => (n0 * w + n1 * u + n2 * v).normalize().into(),
+        None => face_normal,
+    };
+    let normal = if front_face { normal } else { -normal };
+
+    let uv = match uvs {
+        Some([uv0, uv1, uv2]) => [
+            uv0[0] * w + uv1[0] * u + uv2[0] * v,
+            uv0[1] * w + uv1[1] * u + uv2[1] * v,
+        ],
+        None => [u, v],
+    };
+
+    HitRecord::new(ray.at(t), normal, t, uv, front_face, material)
+}
+
+pub fn hit(triangle: &Triangle, ray: Ray, t_min: f32, t_max: f32) -> Option<HitRecord<'_>> {
+    let (t, u, v) = intersect(&triangle.vertices, ray, t_min, t_max)?;
+    Some(record(
+        &triangle.vertices,
+        triangle.normals,
+        triangle.uvs,
+        &triangle.material,
+        ray,
+        t,
+        u,
+        v,
+    ))
+}
+
+/// A uniformly distributed point on the surface and its normal, for sampling lights
+pub fn sample(triangle: &Triangle, sampler: &mut dyn Sampler) -> (Point, Vec3) {
+    let [a, b, c] = triangle.vertices;
+    let [u, v] = primitive::sample_triangle(sampler.get_2d());
+    let point = a * (1.0 - u - v) + b * u + c * v;
+    (point, (b - a).cross(*(c - a)).normalize().into())
+}
+
+pub fn area(triangle: &Triangle) -> f32 {
+    let [a, b, c] = triangle.vertices;
+    0.5 * (b - a).cross(*(c - a)).length()
+}
+
+pub fn bounding_box(triangle: &Triangle) -> Option<Aabb> {
+    Some(vertices_bounding_box(&triangle.vertices))
+}
+
+pub fn vertices_bounding_box(vertices: &[Point; 3]) -> Aabb {
+    // Pad the box so axis-aligned triangles don't end up with a flat box
+    const PADDING: Vec3 = Vec3::new(1e-4, 1e-4, 1e-4);
+
+    let bbox = vertices.iter().fold(Aabb::EMPTY, |b, &v| b.grow(v));
+    Aabb::new(bbox.minimum - PADDING, bbox.maximum + PADDING)
+}
@@ -3,10 +3,12 @@ use std::ops::{Mul, MulAssign};
 use derive_more::{
     Add, AddAssign, Deref, DerefMut, Div, From, Into, Mul, MulAssign, Neg, Sub, SubAssign,
 };
-use image::Rgb;
-use rand::{rngs::ThreadRng, Rng};
+use rand::{rngs::SmallRng, Rng};
 use serde::{Deserialize, Serialize};
 
+/// A 3D vector, used for points, directions and colors alike.
+///
+/// Derefs to [`glam::Vec3`] for the usual vector operations.
 #[derive(
     Add,
     AddAssign,
@@ -36,7 +38,7 @@ impl Vec3 {
         Self(glam::Vec3::new(x, y, z))
     }
 
-    pub fn new_random_range(rng: &mut ThreadRng, _min: f32, _max: f32) -> Self {
+    pub fn new_random_range(rng: &mut SmallRng, _min: f32, _max: f32) -> Self {
         Self(glam::Vec3::new(
             rng.gen_range(_min.._max),
             rng.gen_range(_min.._max),
@@ -44,7 +46,7 @@ impl Vec3 {
         ))
     }
 
-    pub fn new_random(rng: &mut ThreadRng) -> Self {
+    pub fn new_random(rng: &mut SmallRng) -> Self {
         Self(glam::Vec3::new(
             rng.gen::<f32>(),
             rng.gen::<f32>(),
@@ -52,7 +54,7 @@ impl Vec3 {
         ))
     }
 
-    pub fn new_random_in_unit_sphere(rng: &mut ThreadRng) -> Self {
+    pub fn new_random_in_unit_sphere(rng: &mut SmallRng) -> Self {
         let mut p;
         loop {
             p = Self::new_random_range(rng, -1.0, 1.0);
@@ -62,7 +64,7 @@ impl Vec3 {
         }
     }
 
-    pub fn new_random_in_unit_disk(rng: &mut ThreadRng) -> Self {
+    pub fn new_random_in_unit_disk(rng: &mut SmallRng) -> Self {
         let mut p;
         loop {
             p = Vec3::new(rng.gen_range(-1.0..1.0), rng.gen_range(-1.0..1.0), 0.0);
@@ -72,11 +74,11 @@ impl Vec3 {
         }
     }
 
-    pub fn new_random_unit_vector(rng: &mut ThreadRng) -> Self {
+    pub fn new_random_unit_vector(rng: &mut SmallRng) -> Self {
         Self(Self::new_random_in_unit_sphere(rng).normalize())
     }
 
-    pub fn new_random_in_hemisphere(rng: &mut ThreadRng, normal: Vec3) -> Self {
+    pub fn new_random_in_hemisphere(rng: &mut SmallRng, normal: Vec3) -> Self {
         let in_unit_sphere = Self::new_random_in_unit_sphere(rng);
         if in_unit_sphere.dot(*normal) > 0.0 {
             in_unit_sphere
@@ -93,15 +95,6 @@ impl Vec3 {
         self.x.abs() < 1e-8_f32 && self.y.abs() < 1e-8_f32 && self.z.abs() < 1e-8_f32
     }
 
-    pub fn to_rgb(&self, scale: f32) -> [u8; 3] {
-        let f = self.0;
-        [
-            ((f.x * scale).sqrt().clamp(0.0, 0.999) * 255.999) as u8,
-            ((f.y * scale).sqrt().clamp(0.0, 0.999) * 255.999) as u8,
-            ((f.z * scale).sqrt().clamp(0.0, 0.999) * 255.999) as u8,
-        ]
-    }
-
     pub fn reflect(self, normal: &Vec3) -> Self {
         self - *normal * 2.0 * self.dot(**normal)
     }
@@ -129,18 +122,112 @@ impl MulAssign<Vec3> for Vec3 {
     }
 }
 
+/// An orthonormal basis with `w` along a given axis, for turning directions sampled around the z
+/// axis into world space
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Onb {
+    pub u: Vec3,
+    pub v: Vec3,
+    pub w: Vec3,
+}
+
+impl Onb {
+    pub fn new(axis: Vec3) -> Self {
+        let w = axis.normalize();
+        let (u, v) = w.any_orthonormal_pair();
+        Self {
+            u: u.into(),
+            v: v.into(),
+            w: w.into(),
+        }
+    }
+
+    pub fn to_world(&self, local: Vec3) -> Vec3 {
+        self.u * local.x + self.v * local.y + self.w * local.z
+    }
+
+    pub fn to_local(&self, world: Vec3) -> Vec3 {
+        Vec3::new(world.dot(*self.u), world.dot(*self.v), world.dot(*self.w))
+    }
+}
+
+/// Map a point in the unit square to the unit disk in the xy plane, keeping nearby points close
+pub fn sample_concentric_disk(u: [f32; 2]) -> Vec3 {
+    let [x, y] = u.map(|u| 2.0 * u - 1.0);
+    if x == 0.0 && y == 0.0 {
+        return Vec3::ZERO;
+    }
+
+    // Squares around the center map to circles, split into a pair of wedges along each axis
+    let (radius, angle) = if x.abs() > y.abs() {
+        (x, std::f32::consts::FRAC_PI_4 * (y / x))
+    } else {
+        (
+            y,
+            std::f32::consts::FRAC_PI_2 - std::f32::consts::FRAC_PI_4 * (x / y),
+        )
+    };
+    Vec3::new(radius * angle.cos(), radius * angle.sin(), 0.0)
+}
+
+/// Map a point in the unit square to a unit direction around the z axis with a density of
+/// `cos θ / π`, by projecting the disk up onto the hemisphere
+pub fn sample_cosine_hemisphere(u: [f32; 2]) -> Vec3 {
+    let disk = sample_concentric_disk(u);
+    let z = (1.0 - disk.x * disk.x - disk.y * disk.y).max(0.0).sqrt();
+    Vec3::new(disk.x, disk.y, z)
+}
+
+/// Map a point in the unit square to a unit direction with a density of `1 / 4π`
+pub fn sample_uniform_sphere(u: [f32; 2]) -> Vec3 {
+    let z = 1.0 - 2.0 * u[0];
+    let radius = (1.0 - z * z).max(0.0).sqrt();
+    let angle = 2.0 * std::f32::consts::PI * u[1];
+    Vec3::new(radius * angle.cos(), radius * angle.sin(), z)
+}
+
+/// Map a point in the unit cube to a uniformly distributed point in the unit ball
+pub fn sample_uniform_ball(u: [f32; 3]) -> Vec3 {
+    sample_uniform_sphere([u[0], u[1]]) * u[2].cbrt()
+}
+
+/// Map a point in the unit square to a unit direction within `acos(cos_theta_max)` of the z axis,
+/// with a density of `1 / 2π(1 - cos_theta_max)`
+pub fn sample_cone(u: [f32; 2], cos_theta_max: f32) -> Vec3 {
+    let z = 1.0 - u[0] * (1.0 - cos_theta_max);
+    let radius = (1.0 - z * z).max(0.0).sqrt();
+    let angle = 2.0 * std::f32::consts::PI * u[1];
+    Vec3::new(radius * angle.cos(), radius * angle.sin(), z)
+}
+
+/// Map a point in the unit square to a uniformly distributed point in a triangle, as the
+/// barycentric weights of its second and third vertices
+pub fn sample_triangle(u: [f32; 2]) -> [f32; 2] {
+    let s = u[0].sqrt();
+    [s * (1.0 - u[1]), s * u[1]]
+}
+
+/// A position in world space
 pub type Point = Vec3;
+/// A linear RGB color
 pub type Color = Vec3;
 
-#[derive(Clone, Copy, PartialEq)]
+/// A half-line starting at `origin` going towards `direction`, which need not be normalized
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub struct Ray {
     pub origin: Point,
     pub direction: Vec3,
+    /// The moment the ray was sent, which moving objects are positioned at
+    pub time: f32,
 }
 
 impl Ray {
-    pub fn new(origin: Point, direction: Vec3) -> Self {
-        Self { origin, direction }
+    pub fn new(origin: Point, direction: Vec3, time: f32) -> Self {
+        Self {
+            origin,
+            direction,
+            time,
+        }
     }
 
     /// Get the point along the vector at a certain param t
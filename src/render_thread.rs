@@ -0,0 +1,210 @@
+//! Rendering on a separate thread, so the viewer stays responsive during long renders.
+
+use std::{
+    path::PathBuf,
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        mpsc::{self, Receiver, Sender},
+        Arc,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+use image::ImageResult;
+
+use crate::{
+    background::Background, camera::Camera, config::ImageConfig, object::World, primitive::Color,
+    tracer::Tracer,
+};
+
+/// Everything needed to render a pass
+pub struct Job {
+    pub camera: Camera,
+    pub config: ImageConfig,
+    pub background: Background,
+    pub world: Arc<World>,
+    /// Samples per pixel to add to the image
+    pub spp: usize,
+    /// Throw away the samples accumulated so far before rendering
+    pub reset: bool,
+}
+
+enum Message {
+    Render(Job),
+    Save { path: PathBuf, config: ImageConfig },
+}
+
+/// What the render thread reports back to the viewer
+pub enum Update {
+    /// A row of the image is done, counting from the top
+    Row {
+        index: usize,
+        pixels: Vec<Color>,
+    },
+    /// A pass is done, bringing the image to `samples` samples per pixel
+    Finished {
+        samples: usize,
+        render_time: Duration,
+    },
+    Saved(ImageResult<()>),
+}
+
+/// A handle to a thread that renders passes with its own [`Tracer`], streaming rows back as they
+/// finish. The thread stops when the handle is dropped.
+pub struct RenderThread {
+    messages: Sender<Message>,
+    updates: Receiver<Update>,
+    cancel: Arc<AtomicBool>,
+    /// Number of passes queued or in progress
+    pending: Arc<AtomicUsize>,
+}
+
+impl RenderThread {
+    pub fn spawn(tracer: Tracer) -> Self {
+        let (messages, message_receiver) = mpsc::channel();
+        let (update_sender, updates) = mpsc::channel();
+        let cancel = Arc::new(AtomicBool::new(false));
+        let pending = Arc::new(AtomicUsize::new(0));
+
+        let worker = Worker {
+            tracer,
+            messages: message_receiver,
+            updates: update_sender,
+            cancel: cancel.clone(),
+            pending: pending.clone(),
+        };
+        thread::Builder::new()
+            .name("render".to_string())
+            .spawn(move || worker.run())
+            .expect("could not spawn the render thread");
+
+        Self {
+            messages,
+            updates,
+            cancel,
+            pending,
+        }
+    }
+
+    /// Queue a pass. A pass that resets the image makes the one in progress pointless, so that is
+    /// cancelled.
+    pub fn render(&self, job: Job) {
+        if job.reset {
+            self.cancel();
+        }
+        self.pending.fetch_add(1, Ordering::Relaxed);
+        self.messages.send(Message::Render(job)).ok();
+    }
+
+    /// Stop the pass in progress as soon as possible, throwing away the accumulated samples
+    pub fn cancel(&self) {
+        self.cancel.store(true, Ordering::Relaxed);
+    }
+
+    /// Save the image once the pass in progress is done, tone mapped with the settings in
+    /// `config`
+    pub fn save(&self, path: PathBuf, config: ImageConfig) {
+        self.messages.send(Message::Save { path, config }).ok();
+    }
+
+    /// Whether a pass is in progress or queued
+    pub fn is_busy(&self) -> bool {
+        self.pending.load(Ordering::Relaxed) > 0
+    }
+
+    /// The updates received since the last call
+    pub fn updates(&self) -> impl Iterator<Item = Update> + '_ {
+        self.updates.try_iter()
+    }
+}
+
+impl Drop for RenderThread {
+    fn drop(&mut self) {
+        self.cancel();
+    }
+}
+
+struct Worker {
+    tracer: Tracer,
+    messages: Receiver<Message>,
+    updates: Sender<Update>,
+    cancel: Arc<AtomicBool>,
+    pending: Arc<AtomicUsize>,
+}
+
+impl Worker {
+    fn run(mut self) {
+        while let Ok(message) = self.messages.recv() {
+            let mut job = match message {
+                Message::Render(job) => job,
+                Message::Save { path, config } => {
+                    self.save(path, config);
+                    continue;
+                }
+            };
+
+            // Only the newest pass matters if more were queued while the last one rendered
+            let mut taken = 1;
+            while let Ok(message) = self.messages.try_recv() {
+                match message {
+                    Message::Render(next) => {
+                        job = Job {
+                            reset: job.reset || next.reset,
+                            ..next
+                        };
+                        taken += 1;
+                    }
+                    Message::Save { path, config } => self.save(path, config),
+                }
+            }
+
+            self.cancel.store(false, Ordering::Relaxed);
+            if self.render(job, taken).is_err() {
+                // The viewer hung up
+                return;
+            }
+        }
+    }
+
+    /// Render `job`, which stands in for `taken` queued passes
+    fn render(&mut self, job: Job, taken: usize) -> Result<(), mpsc::SendError<Update>> {
+        self.tracer.camera = job.camera;
+        self.tracer.config = job.config;
+        self.tracer.background = job.background;
+        self.tracer.spp = job.spp;
+        if job.reset {
+            self.tracer.reset();
+        }
+
+        let now = Instant::now();
+        let updates = &self.updates;
+        let finished = self
+            .tracer
+            .render_rows(&job.world, &self.cancel, |index, pixels| {
+                updates
+                    .send(Update::Row {
+                        index,
+                        pixels: pixels.to_vec(),
+                    })
+                    .ok();
+            });
+
+        // Passes queued in the meantime keep the count above zero
+        self.pending.fetch_sub(taken, Ordering::Relaxed);
+        if finished {
+            self.updates.send(Update::Finished {
+                samples: self.tracer.samples(),
+                render_time: now.elapsed(),
+            })?;
+        }
+        Ok(())
+    }
+
+    fn save(&mut self, path: PathBuf, config: ImageConfig) {
+        self.tracer.config = config;
+        self.updates
+            .send(Update::Saved(self.tracer.save(path)))
+            .ok();
+    }
+}
@@ -0,0 +1,160 @@
+use std::{fs::File, io::BufWriter, path::Path};
+
+use image::{codecs::hdr::HdrEncoder, ImageResult};
+
+use crate::{config::ImageConfig, primitive::Color, scene::Scene, tracer::Tracer};
+
+/// A rendered image, kept both in linear floating point RGB and as tone mapped 8-bit sRGB for
+/// display
+#[derive(Debug, Clone, PartialEq)]
+pub struct Image {
+    width: u32,
+    height: u32,
+    pixels: Vec<u8>,
+    linear: Vec<f32>,
+}
+
+impl Image {
+    /// Tone map linear pixels with the settings in `config`
+    pub(crate) fn new(config: &ImageConfig, linear: Vec<Color>) -> Self {
+        let (width, height) = (config.width.get(), config.height.get());
+        debug_assert_eq!(linear.len(), width as usize * height as usize);
+        Self {
+            width,
+            height,
+            pixels: linear
+                .iter()
+                .flat_map(|&color| config.tone_mapping.to_rgb8(color, config.exposure))
+                .collect(),
+            linear: linear.iter().flat_map(|color| color.to_array()).collect(),
+        }
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// The pixels in row-major order from the top left, as tightly packed RGB triplets
+    pub fn pixels(&self) -> &[u8] {
+        &self.pixels
+    }
+
+    /// The linear radiance of every pixel in the same layout as [`Image::pixels`], without any
+    /// clamping or gamma
+    pub fn linear_pixels(&self) -> &[f32] {
+        &self.linear
+    }
+
+    /// The color of the pixel at `(x, y)`, counting from the top left
+    pub fn pixel(&self, x: u32, y: u32) -> [u8; 3] {
+        let idx = (y as usize * self.width as usize + x as usize) * 3;
+        [self.pixels[idx], self.pixels[idx + 1], self.pixels[idx + 2]]
+    }
+
+    /// Save the image, with the format determined by the extension of `path`. Radiance `.hdr`
+    /// and OpenEXR `.exr` files get the linear pixels, every other format the tone mapped ones.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> ImageResult<()> {
+        let path = path.as_ref();
+        let extension = path
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .map(str::to_ascii_lowercase);
+
+        match extension.as_deref() {
+            // The image crate can't save Radiance files through the usual path
+            Some("hdr") => {
+                let pixels: Vec<_> = self
+                    .linear
+                    .chunks_exact(3)
+                    .map(|rgb| image::Rgb([rgb[0], rgb[1], rgb[2]]))
+                    .collect();
+                let file = BufWriter::new(File::create(path)?);
+                HdrEncoder::new(file).encode(&pixels, self.width as usize, self.height as usize)
+            }
+            Some("exr") => {
+                image::Rgb32FImage::from_raw(self.width, self.height, self.linear.clone())
+                    .expect("invalid pixel buffer")
+                    .save(path)
+            }
+            _ => image::save_buffer(
+                path,
+                &self.pixels,
+                self.width,
+                self.height,
+                image::ColorType::Rgb8,
+            ),
+        }
+    }
+}
+
+impl From<Image> for image::RgbImage {
+    fn from(image: Image) -> Self {
+        image::RgbImage::from_raw(image.width, image.height, image.pixels)
+            .expect("invalid pixel buffer")
+    }
+}
+
+impl From<Image> for image::Rgb32FImage {
+    fn from(image: Image) -> Self {
+        image::Rgb32FImage::from_raw(image.width, image.height, image.linear)
+            .expect("invalid pixel buffer")
+    }
+}
+
+/// Renders [`Scene`]s into [`Image`]s.
+///
+/// ```no_run
+/// use raytracing::{Config, Renderer, Scene};
+///
+/// let scene = Scene::from(Config::from_file("config.toml")?);
+/// let image = Renderer::new().threads(4).render(&scene);
+/// image.save("render.png")?;
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Renderer {
+    threads: Option<usize>,
+}
+
+impl Renderer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Limit the number of threads used for rendering. By default, every core is used.
+    pub fn threads(mut self, threads: usize) -> Self {
+        self.threads = Some(threads);
+        self
+    }
+
+    /// Render the scene with its image settings
+    pub fn render(&self, scene: &Scene) -> Image {
+        self.render_with_progress(scene, |_| {})
+    }
+
+    /// Render the scene, calling `progress` with the number of finished rows after each row.
+    pub fn render_with_progress<F>(&self, scene: &Scene, progress: F) -> Image
+    where
+        F: Fn(usize) + Sync + Send,
+    {
+        let mut tracer = Tracer::new(scene.image, scene.camera, scene.background.clone());
+        let render = || tracer.render_with_progress(&scene.world, progress);
+
+        match self.threads {
+            Some(threads) => match rayon::ThreadPoolBuilder::new().num_threads(threads).build() {
+                Ok(pool) => pool.install(render),
+                Err(e) => {
+                    tracing::warn!("Could not create a thread pool, using the global one: {e}");
+                    render()
+                }
+            },
+            None => render(),
+        }
+
+        tracer.into_image()
+    }
+}
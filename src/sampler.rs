@@ -0,0 +1,471 @@
+//! Generating the random numbers behind each sample of a pixel.
+
+use rand::{rngs::SmallRng, Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+
+/// The largest `f32` below 1.0
+const ONE_MINUS_EPSILON: f32 = 1.0 - f32::EPSILON / 2.0;
+
+/// Which [`Sampler`] generates the samples of each pixel
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Default)]
+pub enum SamplerType {
+    /// Unrelated random numbers for every sample
+    #[default]
+    Independent,
+    /// Jittered samples spread over a grid of strata, separately for each render
+    Stratified,
+    /// The Halton sequence, randomized with Owen scrambling for each pixel
+    Halton,
+    /// The Sobol sequence, randomized with Owen scrambling for each pixel
+    Sobol,
+}
+
+impl SamplerType {
+    /// A sampler for a render of the `samples_per_pixel` samples numbered from `first_sample`
+    pub fn build(self, seed: u64, first_sample: u64, samples_per_pixel: usize) -> Box<dyn Sampler> {
+        match self {
+            SamplerType::Independent => Box::new(IndependentSampler::new(seed)),
+            SamplerType::Stratified => Box::new(StratifiedSampler::new(
+                seed,
+                first_sample,
+                samples_per_pixel,
+            )),
+            SamplerType::Halton => Box::new(HaltonSampler::new(seed)),
+            SamplerType::Sobol => Box::new(SobolSampler::new(seed)),
+        }
+    }
+}
+
+/// Generates the random numbers for each sample of a pixel, one dimension at a time.
+///
+/// Apart from [`IndependentSampler`], samplers spread each dimension evenly over the samples of a
+/// pixel, which lowers noise as long as every sample uses its dimensions in the same order.
+pub trait Sampler {
+    /// Start the `index`th sample of `pixel`, from its first dimension
+    fn start_pixel_sample(&mut self, pixel: [u32; 2], index: u64);
+
+    /// The next dimension of the current sample, in `[0, 1)`
+    fn get_1d(&mut self) -> f32;
+
+    /// The next two dimensions of the current sample, in `[0, 1)²`
+    fn get_2d(&mut self) -> [f32; 2];
+}
+
+/// Plain random numbers, seeded from the pixel and sample so renders are reproducible no matter
+/// which thread renders which pixel
+pub struct IndependentSampler {
+    seed: u64,
+    rng: SmallRng,
+}
+
+impl IndependentSampler {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            seed,
+            rng: SmallRng::seed_from_u64(seed),
+        }
+    }
+}
+
+impl Sampler for IndependentSampler {
+    fn start_pixel_sample(&mut self, pixel: [u32; 2], index: u64) {
+        let [x, y] = pixel;
+        self.rng = SmallRng::seed_from_u64(hash(&[self.seed, x as u64, y as u64, index]));
+    }
+
+    fn get_1d(&mut self) -> f32 {
+        self.rng.gen()
+    }
+
+    fn get_2d(&mut self) -> [f32; 2] {
+        self.rng.gen()
+    }
+}
+
+/// Kensler's correlated multi-jittered sampling, which stratifies pairs of dimensions over a grid
+/// of any number of samples and each dimension on its own as well
+pub struct StratifiedSampler {
+    seed: u64,
+    /// The first sample of the render, whose samples are spread over the strata
+    first_sample: u64,
+    samples_per_pixel: u32,
+    pixel: [u32; 2],
+    index: u64,
+    dimension: u32,
+}
+
+impl StratifiedSampler {
+    pub fn new(seed: u64, first_sample: u64, samples_per_pixel: usize) -> Self {
+        Self {
+            seed,
+            first_sample,
+            samples_per_pixel: samples_per_pixel.clamp(1, u32::MAX as usize) as u32,
+            pixel: [0, 0],
+            index: 0,
+            dimension: 0,
+        }
+    }
+
+    /// The sample within the current render and a seed for the next dimension of it
+    fn next_dimension(&mut self) -> (u32, u32) {
+        let [x, y] = self.pixel;
+        let n = self.samples_per_pixel as u64;
+        let sample = self.index.saturating_sub(self.first_sample) % n;
+        let seed = hash(&[
+            self.seed,
+            x as u64,
+            y as u64,
+            self.first_sample,
+            self.dimension as u64,
+        ]);
+        self.dimension += 1;
+        (sample as u32, seed as u32)
+    }
+}
+
+impl Sampler for StratifiedSampler {
+    fn start_pixel_sample(&mut self, pixel: [u32; 2], index: u64) {
+        self.pixel = pixel;
+        self.index = index;
+        self.dimension = 0;
+    }
+
+    fn get_1d(&mut self) -> f32 {
+        let (sample, seed) = self.next_dimension();
+        let n = self.samples_per_pixel;
+        let stratum = permute(sample, n, seed.wrapping_mul(0x51633e2d));
+        let jitter = random_float(sample, seed.wrapping_mul(0x967a889b));
+        ((stratum as f32 + jitter) / n as f32).min(ONE_MINUS_EPSILON)
+    }
+
+    fn get_2d(&mut self) -> [f32; 2] {
+        let (sample, seed) = self.next_dimension();
+        let n = self.samples_per_pixel;
+        // A grid of m columns and n rows, where each sample is in its own row and column of the
+        // finer grid within each cell
+        let columns = (n as f32).sqrt() as u32;
+        let rows = n.div_ceil(columns);
+
+        let s = permute(sample, n, seed.wrapping_mul(0x51633e2d));
+        let sx = permute(s % columns, columns, seed.wrapping_mul(0x68bc21eb));
+        let sy = permute(s / columns, rows, seed.wrapping_mul(0x02e5be93));
+        let jx = random_float(s, seed.wrapping_mul(0x967a889b));
+        let jy = random_float(s, seed.wrapping_mul(0x368cc8b7));
+        [
+            (sx as f32 + (sy as f32 + jx) / rows as f32) / columns as f32,
+            (s as f32 + jy) / n as f32,
+        ]
+        .map(|value| value.min(ONE_MINUS_EPSILON))
+    }
+}
+
+/// A pseudo-random permutation of `i` in `[0, len)`, chosen by `seed`
+fn permute(mut i: u32, len: u32, seed: u32) -> u32 {
+    let mut w = len - 1;
+    w |= w >> 1;
+    w |= w >> 2;
+    w |= w >> 4;
+    w |= w >> 8;
+    w |= w >> 16;
+    // Hash within the next power of two, until the result lands in range
+    loop {
+        i ^= seed;
+        i = i.wrapping_mul(0xe170893d);
+        i ^= seed >> 16;
+        i ^= (i & w) >> 4;
+        i ^= seed >> 8;
+        i = i.wrapping_mul(0x0929eb3f);
+        i ^= seed >> 23;
+        i ^= (i & w) >> 1;
+        i = i.wrapping_mul(1 | seed >> 27);
+        i = i.wrapping_mul(0x6935fa69);
+        i ^= (i & w) >> 11;
+        i = i.wrapping_mul(0x74dcb303);
+        i ^= (i & w) >> 2;
+        i = i.wrapping_mul(0x9e501cc3);
+        i ^= (i & w) >> 2;
+        i = i.wrapping_mul(0xc860a3df);
+        i &= w;
+        i ^= i >> 5;
+        if i < len {
+            return (i.wrapping_add(seed)) % len;
+        }
+    }
+}
+
+/// A pseudo-random float in `[0, 1)` for `i`, chosen by `seed`
+fn random_float(mut i: u32, seed: u32) -> f32 {
+    i ^= seed;
+    i ^= i >> 17;
+    i ^= i >> 10;
+    i = i.wrapping_mul(0xb36534e5);
+    i ^= i >> 12;
+    i ^= i >> 21;
+    i = i.wrapping_mul(0x93fc4795);
+    i ^= 0xdf6e307f;
+    i ^= i >> 17;
+    i = i.wrapping_mul(1 | seed >> 18);
+    bits_to_float(i)
+}
+
+/// Enough primes for the dimensions of a few bounces, after which samples are independent
+const PRIMES: [u32; 64] = [
+    2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37, 41, 43, 47, 53, 59, 61, 67, 71, 73, 79, 83, 89, 97,
+    101, 103, 107, 109, 113, 127, 131, 137, 139, 149, 151, 157, 163, 167, 173, 179, 181, 191, 193,
+    197, 199, 211, 223, 227, 229, 233, 239, 241, 251, 257, 263, 269, 271, 277, 281, 283, 293, 307,
+    311,
+];
+
+/// The Halton sequence, with the radical inverse in the `d`th prime base for dimension `d`. Each
+/// pixel scrambles the digits of every dimension, which also breaks up the patterns between
+/// dimensions with similar bases.
+pub struct HaltonSampler {
+    seed: u64,
+    pixel: [u32; 2],
+    index: u64,
+    dimension: u32,
+}
+
+impl HaltonSampler {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            seed,
+            pixel: [0, 0],
+            index: 0,
+            dimension: 0,
+        }
+    }
+}
+
+impl Sampler for HaltonSampler {
+    fn start_pixel_sample(&mut self, pixel: [u32; 2], index: u64) {
+        self.pixel = pixel;
+        self.index = index;
+        self.dimension = 0;
+    }
+
+    fn get_1d(&mut self) -> f32 {
+        let [x, y] = self.pixel;
+        let dimension = self.dimension;
+        self.dimension += 1;
+
+        let seed = hash(&[self.seed, x as u64, y as u64, dimension as u64]);
+        match PRIMES.get(dimension as usize) {
+            Some(&base) => scrambled_radical_inverse(base, self.index, seed),
+            None => bits_to_float(hash(&[seed, self.index]) as u32),
+        }
+    }
+
+    fn get_2d(&mut self) -> [f32; 2] {
+        [self.get_1d(), self.get_1d()]
+    }
+}
+
+/// Mirror the digits of `index` in `base` around the decimal point, permuting each digit depending
+/// on the ones before it
+fn scrambled_radical_inverse(base: u32, mut index: u64, seed: u64) -> f32 {
+    let seed = seed as u32;
+    let digit_seed = |reversed: u64| mix32(seed ^ (reversed as u32).wrapping_mul(0x9e3779b9));
+
+    let (mut reversed, mut scale) = (0u64, 1.0f64);
+    while index > 0 {
+        let digit = (index % base as u64) as u32;
+        reversed = reversed * base as u64 + permute(digit, base, digit_seed(reversed)) as u64;
+        scale /= base as f64;
+        index /= base as u64;
+    }
+    // The leading zeros would be scrambled into random digits, the same as a random offset
+    let jitter = bits_to_float(mix32(digit_seed(reversed))) as f64;
+    (((reversed as f64 + jitter) * scale) as f32).min(ONE_MINUS_EPSILON)
+}
+
+/// The first two dimensions of the Sobol sequence for every pair of dimensions, with the sample
+/// order shuffled and the values Owen scrambled by hashing, as described by Brent Burley in
+/// "Practical Hash-based Owen Scrambling"
+pub struct SobolSampler {
+    seed: u64,
+    pixel: [u32; 2],
+    index: u64,
+    dimension: u32,
+}
+
+impl SobolSampler {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            seed,
+            pixel: [0, 0],
+            index: 0,
+            dimension: 0,
+        }
+    }
+
+    /// A scrambled 2D Sobol point for the next dimensions, using `count` of them
+    fn next_point(&mut self, count: u32) -> [f32; 2] {
+        let [x, y] = self.pixel;
+        let seed = hash(&[self.seed, x as u64, y as u64, self.dimension as u64]);
+        self.dimension += count;
+
+        let [shuffle, scramble_x, scramble_y] = [0, 1, 2].map(|i| hash(&[seed, i]) as u32);
+        let index = nested_uniform_scramble(self.index as u32, shuffle);
+        [
+            nested_uniform_scramble(index.reverse_bits(), scramble_x),
+            nested_uniform_scramble(sobol_second_dimension(index), scramble_y),
+        ]
+        .map(bits_to_float)
+    }
+}
+
+impl Sampler for SobolSampler {
+    fn start_pixel_sample(&mut self, pixel: [u32; 2], index: u64) {
+        self.pixel = pixel;
+        self.index = index;
+        self.dimension = 0;
+    }
+
+    fn get_1d(&mut self) -> f32 {
+        self.next_point(1)[0]
+    }
+
+    fn get_2d(&mut self) -> [f32; 2] {
+        self.next_point(2)
+    }
+}
+
+/// The second dimension of the Sobol sequence as bits after the decimal point, whose generator
+/// matrix is Pascal's triangle mod 2
+fn sobol_second_dimension(mut index: u32) -> u32 {
+    let (mut result, mut direction) = (0, 1 << 31);
+    while index != 0 {
+        if index & 1 != 0 {
+            result ^= direction;
+        }
+        index >>= 1;
+        direction ^= direction >> 1;
+    }
+    result
+}
+
+/// Owen scramble the bits of `x` after the decimal point, flipping each bit depending on the ones
+/// before it
+fn nested_uniform_scramble(x: u32, seed: u32) -> u32 {
+    let mut x = x.reverse_bits();
+    x ^= x.wrapping_mul(0x3d20adea);
+    x = x.wrapping_add(seed);
+    x = x.wrapping_mul((seed >> 16) | 1);
+    x ^= x.wrapping_mul(0x05526c56);
+    x ^= x.wrapping_mul(0x53a22864);
+    x.reverse_bits()
+}
+
+/// The high 24 bits of `bits` as a float in `[0, 1)`
+fn bits_to_float(bits: u32) -> f32 {
+    (bits >> 8) as f32 / (1 << 24) as f32
+}
+
+/// A quick 32-bit hash, from Chris Wellons' hash prospector
+fn mix32(mut x: u32) -> u32 {
+    x ^= x >> 16;
+    x = x.wrapping_mul(0x7feb352d);
+    x ^= x >> 15;
+    x = x.wrapping_mul(0x846ca68b);
+    x ^ (x >> 16)
+}
+
+fn splitmix64(mut x: u64) -> u64 {
+    x = x.wrapping_add(0x9e3779b97f4a7c15);
+    x = (x ^ (x >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+    x = (x ^ (x >> 27)).wrapping_mul(0x94d049bb133111eb);
+    x ^ (x >> 31)
+}
+
+/// Combine `values` into a well mixed hash
+fn hash(values: &[u64]) -> u64 {
+    values
+        .iter()
+        .fold(splitmix64(0), |hash, &value| splitmix64(hash ^ value))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The first `dimensions` values of each of `samples` samples of one pixel, starting at `first`
+    fn values(sampler: &mut dyn Sampler, first: u64, samples: u64, dimensions: usize) -> Vec<f32> {
+        (first..first + samples)
+            .flat_map(|index| {
+                sampler.start_pixel_sample([3, 7], index);
+                (0..dimensions)
+                    .map(|_| sampler.get_1d())
+                    .collect::<Vec<_>>()
+            })
+            .collect()
+    }
+
+    /// Whether `values` fall into each of `n` equal strata exactly once
+    fn is_stratified(values: impl Iterator<Item = f32>, n: usize) -> bool {
+        let mut hits = vec![0; n];
+        for value in values {
+            hits[(value * n as f32) as usize] += 1;
+        }
+        hits.iter().all(|&hits| hits == 1)
+    }
+
+    #[test]
+    fn stratified_hits_every_stratum_once_per_render() {
+        // Renders of different sizes, as in the viewer's first pass followed by smaller ones
+        for (first, n) in [(0, 16), (16, 3), (19, 7), (26, 1), (27, 30)] {
+            let mut sampler = StratifiedSampler::new(5, first, n);
+            let samples: Vec<_> = (first..first + n as u64)
+                .map(|index| {
+                    sampler.start_pixel_sample([3, 7], index);
+                    (sampler.get_1d(), sampler.get_2d(), sampler.get_1d())
+                })
+                .collect();
+
+            assert!(is_stratified(samples.iter().map(|s| s.0), n));
+            assert!(is_stratified(samples.iter().map(|s| s.1[1]), n));
+            assert!(is_stratified(samples.iter().map(|s| s.2), n));
+            let columns = (n as f32).sqrt() as usize;
+            if columns * columns == n {
+                assert!(is_stratified(samples.iter().map(|s| s.1[0]), n));
+            }
+        }
+    }
+
+    #[test]
+    fn low_discrepancy_samples_are_in_range() {
+        for sampler in [SamplerType::Halton, SamplerType::Sobol] {
+            // Enough dimensions to run out of Halton's primes
+            let values = values(sampler.build(1, 0, 64).as_mut(), 0, 64, 80);
+            assert!(values.iter().all(|value| (0.0..1.0).contains(value)));
+        }
+    }
+
+    #[test]
+    fn samples_are_deterministic() {
+        for sampler in [
+            SamplerType::Independent,
+            SamplerType::Stratified,
+            SamplerType::Halton,
+            SamplerType::Sobol,
+        ] {
+            let first = values(sampler.build(1, 0, 16).as_mut(), 0, 16, 10);
+            let again = values(sampler.build(1, 0, 16).as_mut(), 0, 16, 10);
+            let other_seed = values(sampler.build(2, 0, 16).as_mut(), 0, 16, 10);
+            assert_eq!(first, again, "{sampler:?}");
+            assert_ne!(first, other_seed, "{sampler:?}");
+        }
+    }
+
+    #[test]
+    fn low_discrepancy_dimensions_are_stratified() {
+        // Owen scrambling keeps the first 2^k samples of Sobol in separate strata
+        let values = values(SamplerType::Sobol.build(9, 0, 16).as_mut(), 0, 16, 4);
+        for dimension in 0..4 {
+            assert!(is_stratified(
+                values.iter().skip(dimension).step_by(4).copied(),
+                16
+            ));
+        }
+    }
+}
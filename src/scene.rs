@@ -0,0 +1,96 @@
+use crate::{
+    background::Background,
+    config::{CameraConfig, Config, ImageConfig},
+    object::{Object, World},
+};
+
+/// Everything needed to render an image: the objects in the world, the camera looking at them,
+/// the background, and the output image settings.
+///
+/// Scenes are either loaded from a [`Config`] or built programmatically with [`Scene::builder`].
+#[derive(Debug)]
+pub struct Scene {
+    pub camera: CameraConfig,
+    pub image: ImageConfig,
+    pub background: Background,
+    pub world: World,
+}
+
+impl Scene {
+    /// Start building a scene with the given camera and image settings.
+    pub fn builder(camera: CameraConfig, image: ImageConfig) -> SceneBuilder {
+        SceneBuilder {
+            camera,
+            image,
+            background: Background::default(),
+            objects: Vec::new(),
+        }
+    }
+}
+
+impl From<Config> for Scene {
+    fn from(config: Config) -> Self {
+        Self {
+            camera: config.camera,
+            image: config.image,
+            background: config.background,
+            world: World::new(config.world),
+        }
+    }
+}
+
+/// A builder for a [`Scene`], created with [`Scene::builder`].
+///
+/// Materials are attached to the objects that use them, e.g. with [`Sphere::new`].
+///
+/// [`Sphere::new`]: crate::object::Sphere::new
+#[derive(Debug)]
+pub struct SceneBuilder {
+    camera: CameraConfig,
+    image: ImageConfig,
+    background: Background,
+    objects: Vec<Object>,
+}
+
+impl SceneBuilder {
+    pub fn camera(mut self, camera: CameraConfig) -> Self {
+        self.camera = camera;
+        self
+    }
+
+    pub fn image(mut self, image: ImageConfig) -> Self {
+        self.image = image;
+        self
+    }
+
+    pub fn background(mut self, background: Background) -> Self {
+        self.background = background;
+        self
+    }
+
+    /// Add an object, e.g. a [`Sphere`](crate::object::Sphere), to the world.
+    pub fn object(mut self, object: impl Into<Object>) -> Self {
+        self.objects.push(object.into());
+        self
+    }
+
+    /// Add several objects to the world.
+    pub fn objects<I>(mut self, objects: I) -> Self
+    where
+        I: IntoIterator,
+        I::Item: Into<Object>,
+    {
+        self.objects.extend(objects.into_iter().map(Into::into));
+        self
+    }
+
+    /// Finish the scene, building the acceleration structure over its objects.
+    pub fn build(self) -> Scene {
+        Scene {
+            camera: self.camera,
+            image: self.image,
+            background: self.background,
+            world: World::new(self.objects),
+        }
+    }
+}
@@ -1 +1,2 @@
 pub mod rtiow;
+pub mod rtnw;
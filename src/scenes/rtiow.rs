@@ -1,19 +1,26 @@
 use std::num::NonZeroU32;
 
-use rand::thread_rng;
-use rand::Rng;
+use rand::{rngs::SmallRng, Rng, SeedableRng};
 
 use crate::{
+    background::Background,
     config::{CameraConfig, ImageConfig},
     material::{Dielectric, Lambertian, Material, Metal},
     object::{Object, Sphere},
     primitive::{Color, Point},
+    texture::{Checker, Texture},
     Config,
 };
 
-pub fn final_scene() -> Config {
+/// The final scene of "Ray Tracing in One Weekend", with the small spheres placed randomly
+/// according to `seed`.
+pub fn final_scene(seed: u64) -> Config {
     let ground = Material::from(Lambertian {
-        albedo: Color::new(0.5, 0.5, 0.5),
+        albedo: Texture::from(Checker {
+            even: Color::new(0.2, 0.3, 0.1),
+            odd: Color::new(0.9, 0.9, 0.9),
+            scale: 0.32,
+        }),
     });
 
     // -- World --
@@ -21,10 +28,10 @@ pub fn final_scene() -> Config {
     world.push(Object::from(Sphere::new(
         Point::new(0., -1000., 0.),
         1000.,
-        ground.clone(),
+        ground,
     )));
 
-    let mut rng = thread_rng();
+    let mut rng = SmallRng::seed_from_u64(seed);
 
     for _a in -11..11 {
         for _b in -11..11 {
@@ -39,7 +46,9 @@ pub fn final_scene() -> Config {
                     world.push(Object::from(Sphere::new(
                         center,
                         0.2,
-                        Material::from(Lambertian { albedo }),
+                        Material::from(Lambertian {
+                            albedo: albedo.into(),
+                        }),
                     )))
                 } else if choose_mat < 0.95 {
                     world.push(Object::from(Sphere::new(
@@ -65,14 +74,14 @@ pub fn final_scene() -> Config {
         Point::new(-4.0, 1.0, 0.0),
         1.0,
         Material::from(Lambertian {
-            albedo: Color::new(0.4, 0.2, 0.1),
+            albedo: Color::new(0.4, 0.2, 0.1).into(),
         }),
     )));
     world.push(Object::from(Sphere::new(
         Point::new(4.0, 1.0, 0.0),
         1.0,
         Material::from(Metal {
-            albedo: Color::new(0.7, 0.6, 0.5),
+            albedo: Color::new(0.7, 0.6, 0.5).into(),
             fuzz: 0.0,
         }),
     )));
@@ -88,13 +97,23 @@ pub fn final_scene() -> Config {
             vertical_fov: 20.0,
             aperture: 0.1,
             focus_dist: Some(10.0),
+            shutter_open: 0.0,
+            shutter_close: 0.0,
         },
         image: ImageConfig {
             width: NonZeroU32::new(1200).unwrap(),
             height: NonZeroU32::new(800).unwrap(),
             samples_per_pixel: 10,
             max_ray_depth: 50,
+            roulette_depth: 3,
+            roulette_probability: 0.05,
+            seed,
+            sampler: Default::default(),
+            tone_mapping: Default::default(),
+            exposure: 0.0,
         },
+        background: Background::default(),
         world,
+        prototypes: Default::default(),
     }
 }
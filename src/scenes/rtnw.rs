@@ -0,0 +1,129 @@
+use std::{num::NonZeroU32, sync::Arc};
+
+use glam::{Affine3A, Quat};
+
+use crate::{
+    background::Background,
+    config::{CameraConfig, ImageConfig},
+    material::{DiffuseLight, Isotropic, Lambertian, Material},
+    object::{ConstantMedium, Cuboid, Instance, Object, Quad},
+    primitive::{Color, Point, Vec3},
+    Config,
+};
+
+fn lambertian(r: f32, g: f32, b: f32) -> Material {
+    Material::from(Lambertian {
+        albedo: Color::new(r, g, b).into(),
+    })
+}
+
+fn quad(origin: [f32; 3], u: [f32; 3], v: [f32; 3], material: &Material) -> Object {
+    Object::from(Quad::new(
+        Vec3::from(glam::Vec3::from(origin)),
+        Vec3::from(glam::Vec3::from(u)),
+        Vec3::from(glam::Vec3::from(v)),
+        material.clone(),
+    ))
+}
+
+/// The walls of the Cornell box with a square `light` in the ceiling
+fn cornell_walls(light: Object) -> Vec<Object> {
+    let red = lambertian(0.65, 0.05, 0.05);
+    let white = lambertian(0.73, 0.73, 0.73);
+    let green = lambertian(0.12, 0.45, 0.15);
+
+    vec![
+        quad([555., 0., 0.], [0., 555., 0.], [0., 0., 555.], &green),
+        quad([0., 0., 0.], [0., 555., 0.], [0., 0., 555.], &red),
+        light,
+        quad([0., 0., 0.], [555., 0., 0.], [0., 0., 555.], &white),
+        quad([555., 555., 555.], [-555., 0., 0.], [0., 0., -555.], &white),
+        quad([0., 0., 555.], [555., 0., 0.], [0., 555., 0.], &white),
+    ]
+}
+
+/// The tall and the short box standing in the Cornell box, in that order
+fn cornell_boxes(material: &Material) -> [Object; 2] {
+    let rotated_box = |size: Vec3, degrees: f32, translation: Vec3| {
+        let cuboid = Object::from(Cuboid::new(Point::ZERO, size, material.clone()));
+        let transform = Affine3A::from_rotation_translation(
+            Quat::from_rotation_y(degrees.to_radians()),
+            *translation,
+        );
+        Object::from(Instance::new(Arc::new(cuboid), transform).expect("rotations are invertible"))
+    };
+
+    [
+        rotated_box(Vec3::new(165., 330., 165.), 15.0, Vec3::new(265., 0., 295.)),
+        rotated_box(Vec3::new(165., 165., 165.), -18.0, Vec3::new(130., 0., 65.)),
+    ]
+}
+
+fn cornell_config(world: Vec<Object>) -> Config {
+    Config {
+        camera: CameraConfig {
+            look_from: Point::new(278.0, 278.0, -800.0),
+            look_to: Point::new(278.0, 278.0, 0.0),
+            vertical_fov: 40.0,
+            aperture: 0.0,
+            focus_dist: None,
+            shutter_open: 0.0,
+            shutter_close: 0.0,
+        },
+        image: ImageConfig {
+            width: NonZeroU32::new(600).unwrap(),
+            height: NonZeroU32::new(600).unwrap(),
+            samples_per_pixel: 200,
+            max_ray_depth: 50,
+            roulette_depth: 3,
+            roulette_probability: 0.05,
+            seed: 0,
+            sampler: Default::default(),
+            tone_mapping: Default::default(),
+            exposure: 0.0,
+        },
+        background: Background::Off,
+        world,
+        prototypes: Default::default(),
+    }
+}
+
+/// The Cornell box from "Ray Tracing: The Next Week", lit only by the light in its ceiling.
+pub fn cornell_box() -> Config {
+    let light = Material::from(DiffuseLight {
+        color: Color::new(1.0, 1.0, 1.0),
+        intensity: 15.0,
+    });
+
+    let mut world = cornell_walls(quad(
+        [343., 554., 332.],
+        [-130., 0., 0.],
+        [0., 0., -105.],
+        &light,
+    ));
+    world.extend(cornell_boxes(&lambertian(0.73, 0.73, 0.73)));
+    cornell_config(world)
+}
+
+/// The Cornell box with its boxes filled with black and white smoke, under a bigger light.
+pub fn cornell_smoke() -> Config {
+    let light = Material::from(DiffuseLight {
+        color: Color::new(1.0, 1.0, 1.0),
+        intensity: 7.0,
+    });
+
+    let mut world = cornell_walls(quad(
+        [113., 554., 127.],
+        [330., 0., 0.],
+        [0., 0., 305.],
+        &light,
+    ));
+    let [tall, short] = cornell_boxes(&lambertian(0.73, 0.73, 0.73));
+    for (boundary, color) in [(tall, Color::ZERO), (short, Color::new(1.0, 1.0, 1.0))] {
+        let smoke = Material::from(Isotropic {
+            albedo: color.into(),
+        });
+        world.push(Object::from(ConstantMedium::new(boundary, 0.01, smoke)));
+    }
+    cornell_config(world)
+}
@@ -0,0 +1,34 @@
+use serde::{Deserialize, Serialize};
+
+use crate::primitive::{Color, Point};
+
+fn default_scale() -> f32 {
+    1.0
+}
+
+/// A 3D checkerboard of cubes alternating between two colors, which looks the same no matter how
+/// the surface is mapped.
+///
+/// ```toml
+/// Sphere.material.Lambertian.albedo.Checker.even = [0.2, 0.3, 0.1]
+/// Sphere.material.Lambertian.albedo.Checker.odd = [0.9, 0.9, 0.9]
+/// Sphere.material.Lambertian.albedo.Checker.scale = 0.32
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, Serialize)]
+pub struct Checker {
+    pub even: Color,
+    pub odd: Color,
+    /// The size of each cube
+    #[serde(default = "default_scale")]
+    pub scale: f32,
+}
+
+pub fn value(checker: &Checker, point: &Point) -> Color {
+    let cell = (**point / checker.scale).floor();
+    let sum = cell.x as i64 + cell.y as i64 + cell.z as i64;
+    if sum.rem_euclid(2) == 0 {
+        checker.even
+    } else {
+        checker.odd
+    }
+}
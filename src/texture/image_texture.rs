@@ -0,0 +1,138 @@
+use std::{
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use image::{ImageError, Rgb32FImage};
+use serde::{Deserialize, Serialize};
+
+use crate::{primitive::Color, tone_mapping::srgb_to_linear};
+
+/// What happens to surface coordinates outside of `[0, 1]`
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum WrapMode {
+    /// Tile the image
+    #[default]
+    Repeat,
+    /// Tile the image, flipping every other tile so the edges line up
+    MirroredRepeat,
+    /// Stretch the pixels at the edges
+    Clamp,
+}
+
+impl WrapMode {
+    /// Map a texel coordinate onto an axis of the image with `size` texels
+    fn apply(self, x: i64, size: u32) -> u32 {
+        let size = size as i64;
+        let x = match self {
+            WrapMode::Repeat => x.rem_euclid(size),
+            WrapMode::MirroredRepeat => {
+                let x = x.rem_euclid(2 * size);
+                if x < size {
+                    x
+                } else {
+                    2 * size - 1 - x
+                }
+            }
+            WrapMode::Clamp => x.clamp(0, size - 1),
+        };
+        x as u32
+    }
+}
+
+/// The serialized form of an image texture, which is loaded into an [`ImageTexture`]
+#[derive(Deserialize)]
+struct ImageTextureData {
+    path: PathBuf,
+    #[serde(default)]
+    wrap: WrapMode,
+}
+
+impl TryFrom<ImageTextureData> for ImageTexture {
+    type Error = ImageError;
+
+    fn try_from(data: ImageTextureData) -> Result<Self, Self::Error> {
+        let mut texture = ImageTexture::new(data.path)?;
+        texture.wrap = data.wrap;
+        Ok(texture)
+    }
+}
+
+/// An image mapped onto the surface coordinates, with `(0, 0)` at the bottom left of the image.
+///
+/// ```toml
+/// Sphere.material.Lambertian.albedo.Image.path = "earthmap.jpg"
+/// Sphere.material.Lambertian.albedo.Image.wrap = "Clamp" # or "Repeat", "MirroredRepeat"
+/// ```
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[serde(try_from = "ImageTextureData")]
+pub struct ImageTexture {
+    pub path: PathBuf,
+    pub wrap: WrapMode,
+
+    #[serde(skip)]
+    image: Arc<Rgb32FImage>,
+}
+
+impl ImageTexture {
+    pub fn new(path: PathBuf) -> Result<Self, ImageError> {
+        let image = load_linear(&path)?;
+        tracing::info!(
+            path = %path.display(),
+            width = image.width(),
+            height = image.height(),
+            "Loaded texture"
+        );
+
+        Ok(Self {
+            path,
+            wrap: WrapMode::default(),
+            image: Arc::new(image),
+        })
+    }
+}
+
+pub fn value(texture: &ImageTexture, uv: [f32; 2]) -> Color {
+    // Images are stored from the top down
+    let [u, v] = uv;
+    sample_bilinear(&texture.image, u, 1.0 - v, [texture.wrap; 2])
+}
+
+/// Load an image in linear RGB. Anything but floating point images is assumed to be sRGB encoded.
+pub(crate) fn load_linear(path: &Path) -> Result<Rgb32FImage, ImageError> {
+    let image = image::open(path)?;
+
+    let is_linear = matches!(
+        image,
+        image::DynamicImage::ImageRgb32F(_) | image::DynamicImage::ImageRgba32F(_)
+    );
+    let mut image = image.into_rgb32f();
+    if !is_linear {
+        for value in image.iter_mut() {
+            *value = srgb_to_linear(*value);
+        }
+    }
+    Ok(image)
+}
+
+/// Sample an image at `(u, v)`, counting from the top left, by filtering between the four closest
+/// texels. `wrap` is applied to the horizontal and vertical axis respectively.
+pub(crate) fn sample_bilinear(image: &Rgb32FImage, u: f32, v: f32, wrap: [WrapMode; 2]) -> Color {
+    let (width, height) = image.dimensions();
+    let texel = |x: i64, y: i64| {
+        let [r, g, b] = image
+            .get_pixel(wrap[0].apply(x, width), wrap[1].apply(y, height))
+            .0;
+        Color::new(r, g, b)
+    };
+
+    let x = u * width as f32 - 0.5;
+    let y = v * height as f32 - 0.5;
+    let (x0, y0) = (x.floor(), y.floor());
+    let (tx, ty) = (x - x0, y - y0);
+    let (x0, y0) = (x0 as i64, y0 as i64);
+
+    let top = texel(x0, y0) * (1.0 - tx) + texel(x0 + 1, y0) * tx;
+    let bottom = texel(x0, y0 + 1) * (1.0 - tx) + texel(x0 + 1, y0 + 1) * tx;
+    top * (1.0 - ty) + bottom * ty
+}
@@ -0,0 +1,124 @@
+use std::fmt;
+
+use derive_more::From;
+use serde::{
+    de::{
+        value::{MapAccessDeserializer, SeqAccessDeserializer},
+        MapAccess, SeqAccess, Visitor,
+    },
+    Deserialize, Deserializer, Serialize,
+};
+
+use crate::primitive::{Color, Point};
+
+mod checker;
+mod image_texture;
+mod noise;
+mod perlin;
+
+pub use checker::Checker;
+pub use image_texture::{ImageTexture, WrapMode};
+pub use noise::{Noise, NoisePattern};
+pub use perlin::Perlin;
+
+pub(crate) use image_texture::{load_linear, sample_bilinear};
+
+/// Where a material gets its color from at each point of a surface.
+///
+/// In a scene config, a plain color can be used as a shorthand for a solid texture:
+///
+/// ```toml
+/// Sphere.material.Lambertian.albedo = [0.8, 0.3, 0.3]
+/// # is the same as
+/// Sphere.material.Lambertian.albedo.Solid = [0.8, 0.3, 0.3]
+/// ```
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize, From)]
+pub enum Texture {
+    /// The same color everywhere
+    Solid(Color),
+    /// An image mapped onto the surface coordinates
+    Image(ImageTexture),
+    /// A 3D checkerboard
+    Checker(Checker),
+    /// Perlin noise, turbulence or marble
+    Noise(Noise),
+}
+
+impl Texture {
+    /// The color at the surface coordinates `uv` of `point`
+    pub fn value(&self, uv: [f32; 2], point: &Point) -> Color {
+        match self {
+            Texture::Solid(color) => *color,
+            Texture::Image(image) => image_texture::value(image, uv),
+            Texture::Checker(checker) => checker::value(checker, point),
+            Texture::Noise(noise) => noise::value(noise, point),
+        }
+    }
+}
+
+/// Deserialize a texture, also accepting a plain color as a shorthand for a solid texture
+pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Texture, D::Error> {
+    // Not an untagged enum, which would hide why a texture failed to load behind a generic error
+    struct TextureVisitor;
+
+    impl<'de> Visitor<'de> for TextureVisitor {
+        type Value = Texture;
+
+        fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+            formatter.write_str("a color or a texture")
+        }
+
+        fn visit_seq<A: SeqAccess<'de>>(self, seq: A) -> Result<Texture, A::Error> {
+            // A newtype would take the sequence as its fields, so read the inner vector directly
+            let color = glam::Vec3::deserialize(SeqAccessDeserializer::new(seq))?;
+            Ok(Texture::Solid(color.into()))
+        }
+
+        fn visit_map<A: MapAccess<'de>>(self, map: A) -> Result<Texture, A::Error> {
+            Texture::deserialize(MapAccessDeserializer::new(map))
+        }
+    }
+
+    deserializer.deserialize_any(TextureVisitor)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{material::Lambertian, primitive::Color, texture::Texture};
+
+    fn albedo(config: &str) -> Result<Texture, toml::de::Error> {
+        toml::from_str::<Lambertian>(config).map(|lambertian| lambertian.albedo)
+    }
+
+    #[test]
+    fn color_shorthand() {
+        let color = Texture::Solid(Color::new(0.5, 0.25, 1.0));
+        assert_eq!(albedo("albedo = [0.5, 0.25, 1]").unwrap(), color);
+        assert_eq!(albedo("albedo.Solid = [0.5, 0.25, 1]").unwrap(), color);
+        assert!(matches!(
+            albedo("albedo.Checker = { scale = 2, odd = [0, 0, 0], even = [1, 1, 1] }"),
+            Ok(Texture::Checker(_))
+        ));
+    }
+
+    #[test]
+    fn texture_errors_are_kept() {
+        // The I/O error from loading the image, rather than that no variant matched
+        let missing = std::fs::metadata("missing.png").unwrap_err().to_string();
+        let error = albedo(r#"albedo.Image.path = "missing.png""#).unwrap_err();
+        assert!(error.to_string().contains(&missing), "{error}");
+
+        let error = albedo(r#"albedo.Image.pth = "missing.png""#).unwrap_err();
+        assert!(
+            error.to_string().contains("missing field `path`"),
+            "{error}"
+        );
+        let error = albedo("albedo.Wood = {}").unwrap_err();
+        assert!(
+            error.to_string().contains("unknown variant `Wood`"),
+            "{error}"
+        );
+        let error = albedo("albedo = [0.5, 0.25]").unwrap_err();
+        assert!(error.to_string().contains("invalid length 2"), "{error}");
+    }
+}
@@ -0,0 +1,63 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    primitive::{Color, Point},
+    texture::perlin::Perlin,
+};
+
+fn default_scale() -> f32 {
+    1.0
+}
+
+fn default_color() -> Color {
+    Color::new(1.0, 1.0, 1.0)
+}
+
+fn default_octaves() -> u32 {
+    7
+}
+
+/// The kind of pattern made out of the noise
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize, Serialize)]
+pub enum NoisePattern {
+    /// Smooth blobs
+    #[default]
+    Perlin,
+    /// Several octaves of noise summed up, for a rougher look
+    Turbulence,
+    /// Stripes along the z axis, distorted by turbulence
+    Marble,
+}
+
+/// A procedural texture based on Perlin noise.
+///
+/// ```toml
+/// Sphere.material.Lambertian.albedo.Noise = { pattern = "Marble", scale = 4.0 }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, Serialize)]
+pub struct Noise {
+    #[serde(default)]
+    pub pattern: NoisePattern,
+    /// The frequency of the noise, larger values give smaller features
+    #[serde(default = "default_scale")]
+    pub scale: f32,
+    /// The color where the noise is strongest, fading to black
+    #[serde(default = "default_color")]
+    pub color: Color,
+    /// The number of layers summed up for turbulence and marble
+    #[serde(default = "default_octaves")]
+    pub octaves: u32,
+}
+
+pub fn value(noise: &Noise, point: &Point) -> Color {
+    let perlin = Perlin::shared();
+    let point = **point * noise.scale;
+    let intensity = match noise.pattern {
+        NoisePattern::Perlin => 0.5 * (1.0 + perlin.noise(point)),
+        NoisePattern::Turbulence => perlin.turbulence(point, noise.octaves),
+        NoisePattern::Marble => {
+            0.5 * (1.0 + (point.z + 10.0 * perlin.turbulence(point, noise.octaves)).sin())
+        }
+    };
+    noise.color * intensity
+}
@@ -0,0 +1,92 @@
+use std::sync::OnceLock;
+
+use glam::Vec3;
+use rand::{rngs::SmallRng, seq::SliceRandom, Rng, SeedableRng};
+
+const POINT_COUNT: usize = 256;
+
+/// Ken Perlin's gradient noise, with a random unit vector at each lattice point
+pub struct Perlin {
+    gradients: [Vec3; POINT_COUNT],
+    permutations: [[usize; POINT_COUNT]; 3],
+}
+
+impl Perlin {
+    /// The noise every texture shares, generated from a fixed seed so renders are reproducible
+    pub fn shared() -> &'static Perlin {
+        static PERLIN: OnceLock<Perlin> = OnceLock::new();
+        PERLIN.get_or_init(|| Perlin::new(&mut SmallRng::seed_from_u64(0)))
+    }
+
+    pub fn new(rng: &mut SmallRng) -> Self {
+        let gradients = std::array::from_fn(|_| {
+            Vec3::new(
+                rng.gen_range(-1.0..1.0),
+                rng.gen_range(-1.0..1.0),
+                rng.gen_range(-1.0..1.0),
+            )
+            .normalize_or_zero()
+        });
+        let permutations = std::array::from_fn(|_| {
+            let mut permutation = std::array::from_fn(|i| i);
+            permutation.shuffle(rng);
+            permutation
+        });
+
+        Self {
+            gradients,
+            permutations,
+        }
+    }
+
+    /// Smooth noise in roughly `[-1, 1]`
+    pub fn noise(&self, point: Vec3) -> f32 {
+        let cell = point.floor();
+        let offset = point - cell;
+        let [i, j, k] = cell.to_array().map(|c| c as i64);
+
+        let mut corners = [[[Vec3::ZERO; 2]; 2]; 2];
+        for (di, plane) in corners.iter_mut().enumerate() {
+            for (dj, row) in plane.iter_mut().enumerate() {
+                for (dk, corner) in row.iter_mut().enumerate() {
+                    let [px, py, pz] = &self.permutations;
+                    let index = px[((i + di as i64) & 255) as usize]
+                        ^ py[((j + dj as i64) & 255) as usize]
+                        ^ pz[((k + dk as i64) & 255) as usize];
+                    *corner = self.gradients[index];
+                }
+            }
+        }
+
+        // Hermite smoothing to hide the lattice
+        let weights = offset * offset * (3.0 - 2.0 * offset);
+        let mut accumulated = 0.0;
+        for (i, plane) in corners.iter().enumerate() {
+            for (j, row) in plane.iter().enumerate() {
+                for (k, gradient) in row.iter().enumerate() {
+                    let corner = Vec3::new(i as f32, j as f32, k as f32);
+                    let weight = (corner * weights + (1.0 - corner) * (1.0 - weights))
+                        .to_array()
+                        .iter()
+                        .product::<f32>();
+                    accumulated += weight * gradient.dot(offset - corner);
+                }
+            }
+        }
+        accumulated
+    }
+
+    /// The sum of `octaves` layers of noise, each at double the frequency and half the amplitude of
+    /// the last, in roughly `[0, 1]`
+    pub fn turbulence(&self, point: Vec3, octaves: u32) -> f32 {
+        let mut accumulated = 0.0;
+        let mut point = point;
+        let mut weight = 1.0;
+        for _ in 0..octaves {
+            accumulated += weight * self.noise(point);
+            weight *= 0.5;
+            point *= 2.0;
+        }
+        accumulated.abs()
+    }
+}
@@ -0,0 +1,124 @@
+//! Turning the linear radiance the tracer produces into displayable 8-bit sRGB.
+
+use glam::{vec3, Mat3};
+use serde::{Deserialize, Serialize};
+
+use crate::primitive::Color;
+
+fn default_white() -> f32 {
+    4.0
+}
+
+/// How radiance above 1.0 is squeezed into the displayable range
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Default)]
+pub enum ToneMapping {
+    /// Cut off everything above 1.0
+    #[default]
+    Clamp,
+    /// `c / (1 + c)`, which never reaches pure white
+    Reinhard,
+    /// Reinhard, scaled so that `white` maps to pure white
+    ExtendedReinhard {
+        #[serde(default = "default_white")]
+        white: f32,
+    },
+    /// The ACES filmic curve, as fitted by Stephen Hill
+    Aces,
+    /// Troy Sobotka's AgX, which desaturates bright colors towards white instead of skewing hues
+    AgX,
+}
+
+impl ToneMapping {
+    /// Map linear radiance to linear values in `[0, 1]` for display
+    pub fn apply(self, color: Color) -> Color {
+        let color = color.max(glam::Vec3::ZERO);
+        let mapped = match self {
+            ToneMapping::Clamp => color,
+            ToneMapping::Reinhard => color / (color + 1.0),
+            ToneMapping::ExtendedReinhard { white } => {
+                color * (color / (white * white) + 1.0) / (color + 1.0)
+            }
+            ToneMapping::Aces => aces(color),
+            ToneMapping::AgX => agx(color),
+        };
+        mapped.clamp(glam::Vec3::ZERO, glam::Vec3::ONE).into()
+    }
+
+    /// Expose, tone map and sRGB encode linear radiance into an 8-bit pixel. `exposure` is in
+    /// stops, so each +1 doubles the brightness.
+    pub fn to_rgb8(self, color: Color, exposure: f32) -> [u8; 3] {
+        let mapped = self.apply(color * exposure.exp2());
+        mapped
+            .to_array()
+            .map(|value| (linear_to_srgb(value) * 255.0).round() as u8)
+    }
+}
+
+/// The sRGB transfer function, from linear light to encoded values
+pub fn linear_to_srgb(value: f32) -> f32 {
+    if value <= 0.0031308 {
+        value * 12.92
+    } else {
+        1.055 * value.powf(1.0 / 2.4) - 0.055
+    }
+}
+
+/// The inverse sRGB transfer function, from encoded values to linear light
+pub fn srgb_to_linear(value: f32) -> f32 {
+    if value <= 0.04045 {
+        value / 12.92
+    } else {
+        ((value + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+fn aces(color: glam::Vec3) -> glam::Vec3 {
+    // sRGB => XYZ => D65_2_D60 => AP1 => RRT_SAT
+    let input = Mat3::from_cols(
+        vec3(0.59719, 0.07600, 0.02840),
+        vec3(0.35458, 0.90834, 0.13383),
+        vec3(0.04823, 0.01566, 0.83777),
+    );
+    // ODT_SAT => XYZ => D60_2_D65 => sRGB
+    let output = Mat3::from_cols(
+        vec3(1.60475, -0.10208, -0.00327),
+        vec3(-0.53108, 1.10813, -0.07276),
+        vec3(-0.07367, -0.00605, 1.07602),
+    );
+
+    let v = input * color;
+    let v = (v * (v + 0.0245786) - 0.000090537) / (v * (0.983729 * v + 0.432951) + 0.238081);
+    output * v
+}
+
+fn agx(color: glam::Vec3) -> glam::Vec3 {
+    const MIN_EV: f32 = -12.47393;
+    const MAX_EV: f32 = 4.026069;
+
+    let inset = Mat3::from_cols(
+        vec3(0.84247906, 0.042328242, 0.042375655),
+        vec3(0.0784336, 0.87846864, 0.0784336),
+        vec3(0.079223745, 0.07916613, 0.879143),
+    );
+    let outset = Mat3::from_cols(
+        vec3(1.196879, -0.052896852, -0.052971636),
+        vec3(-0.09802088, 1.1519031, -0.09804345),
+        vec3(-0.09902974, -0.098961177, 1.1510737),
+    );
+
+    // Log encode into [0, 1], then apply a polynomial fit of the AgX base contrast curve
+    let v = inset * color;
+    let v = v
+        .max(glam::Vec3::splat(1e-10))
+        .to_array()
+        .map(|v| (v.log2().clamp(MIN_EV, MAX_EV) - MIN_EV) / (MAX_EV - MIN_EV));
+    let v = glam::Vec3::from_array(v.map(|x| {
+        let x2 = x * x;
+        let x4 = x2 * x2;
+        15.5 * x4 * x2 - 40.14 * x4 * x + 31.96 * x4 - 6.868 * x2 * x + 0.4298 * x2 + 0.1191 * x
+            - 0.00232
+    }));
+
+    // The curve produces display encoded values, so linearize them again
+    (outset * v).max(glam::Vec3::ZERO).powf(2.2)
+}
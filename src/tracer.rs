@@ -1,26 +1,42 @@
-use std::path::Path;
+use std::{
+    path::Path,
+    sync::atomic::{AtomicBool, AtomicUsize, Ordering},
+};
 
 use image::ImageResult;
-use rand::{rngs::ThreadRng, thread_rng, Rng};
 use rayon::prelude::{ParallelBridge, ParallelIterator};
 
 use crate::{
+    background::Background,
     camera::Camera,
     config::{CameraConfig, ImageConfig},
-    object::{Hittable, Object},
-    primitive::{Color, Ray},
+    object::{HitRecord, Hittable, World},
+    primitive::{Color, Point, Ray, Vec3},
+    renderer::Image,
+    sampler::Sampler,
 };
 
+/// The stateful renderer behind the interactive viewer, which keeps its pixel buffer and camera
+/// around between renders. For one-off renders, use [`Renderer`](crate::Renderer) instead.
+///
+/// Successive renders are averaged together so the image converges over time. Call
+/// [`Tracer::reset`] whenever the scene changes to start over.
 pub struct Tracer {
-    pixels: Vec<u8>,
+    /// The average of the accumulated samples for each pixel, in linear RGB
+    pixels: Vec<Color>,
+    /// Sum of every sample taken for each pixel since the last reset
+    accumulation: Vec<Color>,
+    /// Number of samples per pixel in `accumulation`
+    samples: usize,
     pub camera: Camera,
     pub config: ImageConfig,
+    pub background: Background,
 
     pub spp: usize,
 }
 
 impl Tracer {
-    pub fn new(config: ImageConfig, camera: CameraConfig) -> Self {
+    pub fn new(config: ImageConfig, camera: CameraConfig, background: Background) -> Self {
         let width = config.width.get() as usize;
         let height = config.height.get() as usize;
 
@@ -33,87 +49,294 @@ impl Tracer {
         Self {
             config,
             camera,
-            pixels: vec![0; width * height * 3],
+            background,
+            pixels: vec![Color::ZERO; width * height],
+            accumulation: vec![Color::ZERO; width * height],
+            samples: 0,
             spp: config.samples_per_pixel,
         }
     }
 
-    pub fn save<P: AsRef<Path>>(&mut self, path: P) -> ImageResult<()> {
-        let image = image::ImageBuffer::<image::Rgb<u8>, &[u8]>::from_raw(
-            self.config.width.get(),
-            self.config.height.get(),
-            &self.pixels,
-        )
-        .expect("invalid pixel buffer");
-        image.save(path)
+    /// Save the rendered image. Radiance `.hdr` and OpenEXR `.exr` files keep the full dynamic
+    /// range, other formats are tone mapped to 8 bits.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> ImageResult<()> {
+        Image::new(&self.config, self.pixels.clone()).save(path)
+    }
+
+    /// The rendered image
+    pub fn into_image(self) -> Image {
+        Image::new(&self.config, self.pixels)
+    }
+
+    /// The rendered image in linear RGB, in row-major order from the top left
+    pub fn pixels(&self) -> &[Color] {
+        &self.pixels
+    }
+
+    /// Number of samples per pixel accumulated so far
+    pub fn samples(&self) -> usize {
+        self.samples
+    }
+
+    /// Throw away the accumulated samples, e.g. because the camera or scene changed
+    pub fn reset(&mut self) {
+        self.accumulation.fill(Color::ZERO);
+        self.samples = 0;
     }
 
-    pub fn buffer_mut(&mut self) -> &mut [u8] {
-        &mut self.pixels
+    /// Render another `spp` samples per pixel and add them to the accumulated image
+    pub fn render(&mut self, world: &World) {
+        self.render_with_progress(world, |_| {});
     }
 
-    pub fn render(&mut self, world: &[Object]) {
+    /// Render the world, calling `progress` with the number of finished rows after each row.
+    pub fn render_with_progress<F>(&mut self, world: &World, progress: F)
+    where
+        F: Fn(usize) + Sync,
+    {
+        let finished_rows = AtomicUsize::new(0);
+        self.render_rows(world, &AtomicBool::new(false), |_, _| {
+            progress(finished_rows.fetch_add(1, Ordering::Relaxed) + 1)
+        });
+    }
+
+    /// Render the world, calling `row_finished` with the index (counting from the top) and linear
+    /// pixels of each row as soon as it is done.
+    ///
+    /// Returns `false` if the render was stopped early by setting `cancel`, in which case the
+    /// accumulated samples are thrown away.
+    pub fn render_rows<F>(&mut self, world: &World, cancel: &AtomicBool, row_finished: F) -> bool
+    where
+        F: Fn(usize, &[Color]) + Sync,
+    {
         let width = self.config.width.get() as usize;
         let height = self.config.height.get() as usize;
+        let scale = 1.0 / (self.samples + self.spp) as f32;
 
         self.pixels
-            .chunks_exact_mut(width as usize * 3)
+            .chunks_exact_mut(width)
+            .zip(self.accumulation.chunks_exact_mut(width))
             .enumerate()
             .into_iter()
             .par_bridge()
-            .for_each(|(j, row)| {
-                let j = height - j - 1;
-                let mut row_idx = 0;
+            .for_each(|(row_index, (row, accumulated_row))| {
+                if cancel.load(Ordering::Relaxed) {
+                    return;
+                }
+
+                let j = height - row_index - 1;
+                let mut sampler =
+                    self.config
+                        .sampler
+                        .build(self.config.seed, self.samples as u64, self.spp);
 
-                let mut rng = thread_rng();
                 (0..width).for_each(|i| {
                     let mut pixel_color = Color::new(0.0, 0.0, 0.0);
-                    for _ in 0..self.spp {
-                        let u = (i as f32 + rng.gen::<f32>()) / (width - 1) as f32;
-                        let v = (j as f32 + rng.gen::<f32>()) / (height - 1) as f32;
+                    for s in 0..self.spp {
+                        // Number the samples across renders, so they keep filling in the gaps
+                        let index = (self.samples + s) as u64;
+                        sampler.start_pixel_sample([i as u32, j as u32], index);
+
+                        let [du, dv] = sampler.get_2d();
+                        let u = (i as f32 + du) / (width - 1) as f32;
+                        let v = (j as f32 + dv) / (height - 1) as f32;
 
-                        let ray = self.camera.get_ray(&mut rng, u, v);
-                        pixel_color +=
-                            Tracer::ray_color(&mut rng, ray, world, self.config.max_ray_depth);
+                        let ray = self.camera.get_ray(sampler.as_mut(), u, v);
+                        pixel_color += Tracer::ray_color(
+                            sampler.as_mut(),
+                            ray,
+                            world,
+                            &self.background,
+                            &self.config,
+                        );
                     }
 
-                    let color = {
-                        let scale = 1.0 / self.spp as f32;
-                        pixel_color.to_rgb(scale)
-                    };
-                    row[row_idx..(row_idx + 3)].copy_from_slice(&color);
-                    row_idx += 3;
+                    accumulated_row[i] += pixel_color;
+                    row[i] = accumulated_row[i] * scale;
                 });
+
+                row_finished(row_index, row);
             });
+
+        if cancel.load(Ordering::Relaxed) {
+            self.reset();
+            return false;
+        }
+
+        self.samples += self.spp;
+        true
     }
 
-    fn ray_color(rng: &mut ThreadRng, ray: Ray, world: &[Object], depth: i32) -> Color {
+    fn ray_color(
+        sampler: &mut dyn Sampler,
+        ray: Ray,
+        world: &World,
+        background: &Background,
+        config: &ImageConfig,
+    ) -> Color {
         let mut result = Color::new(0.0, 0.0, 0.0);
         let mut global_attenuation = Color::new(1.0, 1.0, 1.0);
 
         let mut current_ray = ray;
+        // Where the current ray was scattered from and the density of its direction, if the light
+        // reaching that point was also sampled directly
+        let mut last_scatter: Option<(Point, f32)> = None;
 
-        let white = Color::new(1.0, 1.0, 1.0);
-        let blue = Color::new(0.5, 0.7, 1.0);
+        for bounce in 0..config.max_ray_depth {
+            let mut hit = world.hit(current_ray, 0.001, f32::MAX);
+
+            // Sample how far the ray gets through each medium in its way before scattering, and
+            // scatter off the closest one if that happens before reaching the next surface
+            let t_max = hit.map_or(f32::MAX, |record| record.t);
+            let scattered = world
+                .media()
+                .filter_map(|medium| {
+                    let (enter, exit) = medium.interval(current_ray, 0.001, t_max)?;
+                    let distance = -(1.0 - sampler.get_1d()).ln() / medium.density;
+                    let t = enter + distance / current_ray.direction.length();
+                    (t < exit).then_some((t, medium))
+                })
+                .min_by(|(a, _), (b, _)| a.total_cmp(b));
+            if let Some((t, medium)) = scattered {
+                hit = Some(medium.scatter_record(current_ray, t));
+            }
+
+            if let Some(record) = hit {
+                // Lights could also have been found by sampling them directly at the last surface
+                let weight = match (last_scatter, record.light) {
+                    (Some((origin, pdf)), Some(light)) => {
+                        let light_pdf = world.light_pdf(light, origin, record.point, record.normal);
+                        power_heuristic(pdf, light_pdf)
+                    }
+                    _ => 1.0,
+                };
+                result += global_attenuation * record.material.emitted(&record) * weight;
+
+                if let Some(res) = record.material.scatter(sampler, &current_ray, &record) {
+                    if res.pdf.is_some() {
+                        result += global_attenuation
+                            * Tracer::direct_light(sampler, world, &current_ray, &record);
+                    }
+                    last_scatter = res.pdf.map(|pdf| (record.point, pdf));
 
-        for _ in 0..depth {
-            if let Some(record) = world.hit(current_ray, 0.001, f32::MAX) {
-                if let Some(res) = record.material.scatter(rng, &current_ray, &record) {
                     global_attenuation *= res.attenuation;
                     current_ray = res.ray;
+
+                    // Terminate paths at random, more likely the less light they carry, and make
+                    // up for it by boosting the ones that survive
+                    if bounce >= config.roulette_depth {
+                        let survival = global_attenuation
+                            .max_element()
+                            .min(1.0 - config.roulette_probability);
+                        if sampler.get_1d() >= survival {
+                            break;
+                        }
+                        global_attenuation *= 1.0 / survival;
+                    }
                 } else {
                     break;
                 }
             } else {
-                let unit_direction = current_ray.direction.normalize();
-                let t = 0.5 * (unit_direction.y + 1.0);
-
-                let color = white * (1.0 - t) + blue * t;
-                result += global_attenuation * color;
+                result += global_attenuation * background.color(&current_ray);
                 break;
             }
         }
 
         result
     }
+
+    /// Sample the light scattered along `r_in` reversed that comes straight from one of the
+    /// lights, weighted against finding the light by scattering
+    fn direct_light(
+        sampler: &mut dyn Sampler,
+        world: &World,
+        r_in: &Ray,
+        record: &HitRecord,
+    ) -> Color {
+        let Some(light) = world.sample_light(sampler, record.point) else {
+            return Color::ZERO;
+        };
+        if !light.pdf.is_finite() || light.pdf <= 0.0 {
+            return Color::ZERO;
+        }
+
+        let to_light = light.point - record.point;
+        let distance = to_light.length();
+        let direction: Vec3 = (*to_light / distance).into();
+        let bsdf = record.material.eval(r_in, record, direction);
+        if bsdf.max_element() <= 0.0 {
+            return Color::ZERO;
+        }
+
+        // Anything between the surface and the light casts a shadow, with the same offsets as other
+        // rays so that nearby occluders aren't skipped
+        let shadow_ray = Ray::new(record.point, direction, r_in.time);
+        if world.hit(shadow_ray, 0.001, distance - 0.001).is_some() {
+            return Color::ZERO;
+        }
+        let transmittance = world.transmittance(shadow_ray, 0.001, distance - 0.001);
+        let weight = power_heuristic(light.pdf, record.material.pdf(r_in, record, direction));
+
+        let light_record = HitRecord::new(
+            light.point,
+            light.normal,
+            distance,
+            [0.0, 0.0],
+            light.normal.dot(*to_light) < 0.0,
+            light.material,
+        );
+        light.material.emitted(&light_record) * bsdf * (transmittance * weight / light.pdf)
+    }
+}
+
+/// The weight of a sample taken with a density of `pdf`, when it could also have been taken with
+/// a density of `other`
+fn power_heuristic(pdf: f32, other: f32) -> f32 {
+    let (a, b) = (pdf * pdf, other * other);
+    a / (a + b)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::num::NonZeroU32;
+
+    use super::*;
+    use crate::{sampler::SamplerType, scenes::rtnw};
+
+    /// Render a small version of the smoky Cornell box on `threads` threads
+    fn render(threads: usize, seed: u64, sampler: SamplerType) -> Vec<Color> {
+        let mut config = rtnw::cornell_smoke();
+        config.image.width = NonZeroU32::new(24).unwrap();
+        config.image.height = NonZeroU32::new(16).unwrap();
+        config.image.samples_per_pixel = 2;
+        config.image.seed = seed;
+        config.image.sampler = sampler;
+
+        let world = World::new(config.world);
+        let mut tracer = Tracer::new(config.image, config.camera, config.background);
+        let pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(threads)
+            .build()
+            .unwrap();
+        // Two passes, so accumulation is covered too
+        pool.install(|| {
+            tracer.render(&world);
+            tracer.render(&world);
+        });
+        tracer.pixels().to_vec()
+    }
+
+    #[test]
+    fn renders_are_identical_on_any_number_of_threads() {
+        for sampler in [
+            SamplerType::Independent,
+            SamplerType::Stratified,
+            SamplerType::Halton,
+            SamplerType::Sobol,
+        ] {
+            let single = render(1, 7, sampler);
+            assert_eq!(single, render(4, 7, sampler), "{sampler:?}");
+            assert_ne!(single, render(4, 8, sampler), "{sampler:?}");
+        }
+    }
 }